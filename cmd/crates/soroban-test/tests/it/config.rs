@@ -1,6 +1,6 @@
 use assert_fs::TempDir;
 use soroban_test::{AssertExt, TestEnv};
-use std::{fs, path::Path};
+use std::{fs, path::Path, str::FromStr};
 
 use crate::util::{add_key, add_test_id, SecretKind, DEFAULT_SEED_PHRASE};
 use soroban_cli::commands::network;
@@ -259,3 +259,387 @@ fn use_env() {
         .success()
         .stdout("SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD\n");
 }
+
+#[test]
+fn muxed_identity() {
+    let sandbox = TestEnv::default();
+    sandbox
+        .new_assert_cmd("keys")
+        .env(
+            "SOROBAN_SECRET_KEY",
+            "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD",
+        )
+        .args(["add", "bob", "--mux-id", "7"])
+        .assert()
+        .success();
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/bob.toml")).unwrap();
+    assert_eq!(
+        file_contents,
+        "secret_key = \"SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD\"\nmux_id = 7\n"
+    );
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["show", "bob"])
+        .assert()
+        .success()
+        .stdout("SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD\n");
+    let muxed = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "bob"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    assert!(muxed.starts_with('M'));
+    let account = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", &muxed])
+        .assert()
+        .success()
+        .stdout_as_str();
+    assert!(account.starts_with('G'));
+}
+
+#[test]
+fn encrypted_identity() {
+    let sandbox = TestEnv::default();
+    let secret_key = "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD";
+    sandbox
+        .new_assert_cmd("keys")
+        .env("SOROBAN_SECRET_KEY", secret_key)
+        .args(["add", "bob", "--mux-id", "7"])
+        .assert()
+        .success();
+    sandbox
+        .new_assert_cmd("keys")
+        .env("SOROBAN_SECRET_KEY", secret_key)
+        .env("STELLAR_IDENTITY_PASSPHRASE", "correct horse")
+        .args(["add", "alice", "--encrypt"])
+        .assert()
+        .success();
+    let address = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "alice"])
+        .assert()
+        .success()
+        .stdout_as_str();
+
+    sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_IDENTITY_PASSPHRASE", "correct horse")
+        .arg("encrypt")
+        .assert()
+        .success()
+        .stderr("Encrypted bob\n");
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/bob.toml")).unwrap();
+    assert!(!file_contents.contains(secret_key));
+    assert!(file_contents.contains(&format!("public_key = \"{address}\"")));
+    assert!(file_contents.ends_with("mux_id = 7\n"));
+
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "bob"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("M"));
+    sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_IDENTITY_PASSPHRASE", "correct horse")
+        .args(["show", "bob"])
+        .assert()
+        .success()
+        .stdout(format!("{secret_key}\n"));
+    sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_IDENTITY_PASSPHRASE", "battery staple")
+        .args(["show", "alice"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("wrong passphrase"));
+}
+
+#[test]
+fn encrypted_seed_phrase_with_passphrase() {
+    let sandbox = TestEnv::default();
+    let address = sep5::SeedPhrase::from_str(DEFAULT_SEED_PHRASE)
+        .unwrap()
+        .from_path_index(0, Some("correct horse"))
+        .unwrap()
+        .public()
+        .to_string();
+    fs::create_dir_all(sandbox.dir().join(".soroban/identity")).unwrap();
+    fs::write(
+        sandbox.dir().join(".soroban/identity/carol.toml"),
+        format!("public_key = \"{address}\"\nseed_phrase = \"{DEFAULT_SEED_PHRASE}\"\n"),
+    )
+    .unwrap();
+    let secret_key = sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_MNEMONIC_PASSPHRASE", "correct horse")
+        .args(["show", "carol"])
+        .assert()
+        .success()
+        .stdout_as_str();
+
+    sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_IDENTITY_PASSPHRASE", "battery staple")
+        .env("STELLAR_MNEMONIC_PASSPHRASE", "correct horse")
+        .arg("encrypt")
+        .assert()
+        .success()
+        .stderr("Encrypted carol\n");
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/carol.toml")).unwrap();
+    assert!(!file_contents.contains(DEFAULT_SEED_PHRASE));
+    assert!(!file_contents.contains("correct horse"));
+    assert!(file_contents.contains(&format!("public_key = \"{address}\"")));
+
+    sandbox
+        .new_assert_cmd("keys")
+        .env("STELLAR_IDENTITY_PASSPHRASE", "battery staple")
+        .args(["show", "carol"])
+        .assert()
+        .success()
+        .stdout(format!("{secret_key}\n"));
+}
+
+#[test]
+fn env_var_identity() {
+    let sandbox = TestEnv::default();
+    let secret_key = "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD";
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["add", "ci", "--secret-env", "CI_SECRET_KEY"])
+        .assert()
+        .success();
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/ci.toml")).unwrap();
+    assert_eq!(file_contents, "secret_env = \"CI_SECRET_KEY\"\n");
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["ls", "-l"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Name: ci\nBackend: env\n"));
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "ci"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("CI_SECRET_KEY"));
+    sandbox
+        .new_assert_cmd("keys")
+        .env("CI_SECRET_KEY", secret_key)
+        .args(["show", "ci"])
+        .assert()
+        .success()
+        .stdout(format!("{secret_key}\n"));
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["rm", "ci"])
+        .assert()
+        .success();
+    assert!(!sandbox.dir().join(".soroban/identity/ci.toml").exists());
+}
+
+#[test]
+fn watch_only_identity() {
+    let sandbox = TestEnv::default();
+    let address = "GABISKK4K5VQK3XZKVQMU3TBAB7QCVES4NYLNQYBA73M7VDO4UXEMGFX";
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["add", "partner", "--public-key", address])
+        .assert()
+        .success();
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/partner.toml")).unwrap();
+    assert_eq!(file_contents, format!("public_key = \"{address}\"\n"));
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "partner"])
+        .assert()
+        .success()
+        .stdout(format!("{address}\n"));
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["show", "partner"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("address-only identity"));
+    sandbox
+        .new_assert_cmd("tx")
+        .args([
+            "new",
+            "payment",
+            "--source",
+            "partner",
+            "--destination",
+            address,
+            "--amount",
+            "10",
+            "--build-only",
+            "--sequence",
+            "5",
+        ])
+        .assert()
+        .success();
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["add", "bad", "--public-key", "GABC"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn contract_alias() {
+    let sandbox = TestEnv::default();
+    let contract_id = "CAS3J7GYLGXMF6TDJBBYYSE3HQ6BBSMLNUQ34T6TZMYMW2EVH34XOWMA";
+    let network = [
+        "--rpc-url",
+        "https://example.com",
+        "--network-passphrase",
+        "Alias Network",
+    ];
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "add", "token", "--id", contract_id])
+        .args(network)
+        .assert()
+        .success();
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/contract-ids/token.toml")).unwrap();
+    assert_eq!(
+        file_contents,
+        format!("[ids]\n\"Alias Network\" = \"{contract_id}\"\n")
+    );
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "show", "token"])
+        .args(network)
+        .assert()
+        .success()
+        .stdout(format!("{contract_id}\n"));
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "show", "token"])
+        .args(["--rpc-url", "https://example.com"])
+        .args(["--network-passphrase", "Other Network"])
+        .assert()
+        .failure();
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "ls"])
+        .assert()
+        .success()
+        .stdout(format!("token: {contract_id} (Alias Network)\n"));
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "add", "not/an/alias", "--id", contract_id])
+        .args(network)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("can only contain"));
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "add", "cafe", "--id", contract_id])
+        .args(network)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("would be read as a contract ID"));
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["alias", "rm", "token"])
+        .args(network)
+        .assert()
+        .success();
+    assert!(!sandbox
+        .dir()
+        .join(".soroban/contract-ids/token.toml")
+        .exists());
+}
+
+#[test]
+fn generate_24_words() {
+    let sandbox = TestEnv::default();
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["generate", "--no-fund", "--words", "24", "long_seed"])
+        .assert()
+        .success();
+    let file_contents =
+        fs::read_to_string(sandbox.dir().join(".soroban/identity/long_seed.toml")).unwrap();
+    let seed_phrase = file_contents
+        .trim()
+        .strip_prefix("seed_phrase = \"")
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap();
+    assert_eq!(seed_phrase.split(' ').count(), 24);
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["generate", "--no-fund", "--words", "18", "bad_seed"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn ls_json() {
+    let sandbox = TestEnv::default();
+    let secret_key = "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD";
+    let address = "GABISKK4K5VQK3XZKVQMU3TBAB7QCVES4NYLNQYBA73M7VDO4UXEMGFX";
+    sandbox
+        .new_assert_cmd("keys")
+        .env("SOROBAN_SECRET_KEY", secret_key)
+        .args(["add", "alice", "--secret-key"])
+        .assert()
+        .success();
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["add", "partner", "--public-key", address])
+        .assert()
+        .success();
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["generate", "--no-fund", "--default-seed", "seed"])
+        .assert()
+        .success();
+    let output = sandbox
+        .new_assert_cmd("keys")
+        .args(["ls", "--output", "json"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    assert!(!output.contains(secret_key));
+    let identities: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    let identity = |name: &str| {
+        identities
+            .iter()
+            .find(|identity| identity["name"] == name)
+            .unwrap()
+            .clone()
+    };
+    let alice = identity("alice");
+    assert_eq!(alice["secret_type"], "secret key");
+    assert_eq!(alice["backend"], "file");
+    assert_eq!(alice["location"], "local");
+    assert!(alice["hd_path"].is_null());
+    assert_eq!(
+        alice["address"],
+        "GA4UDP4BFQHDUT3SUIKDHFQXXS7TFQ3NGXLPYTZBWZI6MHMB35KPTA2K"
+    );
+    let partner = identity("partner");
+    assert_eq!(partner["secret_type"], "watch-only");
+    assert_eq!(partner["address"], address);
+    let seed = identity("seed");
+    assert_eq!(seed["secret_type"], "seed phrase");
+    assert_eq!(seed["hd_path"], 0);
+    sandbox
+        .new_assert_cmd("keys")
+        .args(["ls", "-l"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!(
+            "Name: partner\nBackend: public key\nAddress: {address}\nType: watch-only\nHD path: none\nLocation: local\n"
+        )));
+}
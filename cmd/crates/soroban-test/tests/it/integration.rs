@@ -1,5 +1,6 @@
 mod custom_types;
 mod dotenv;
 mod hello_world;
+mod tx;
 mod util;
 mod wrap;
@@ -0,0 +1,457 @@
+use soroban_env_host::xdr::{Limits, ReadXdr, TransactionEnvelope};
+use soroban_test::{AssertExt, TestEnv};
+
+use crate::integration::util::{deploy_hello, HELLO_WORLD};
+use crate::util::DEFAULT_CONTRACT_ID;
+
+#[tokio::test]
+async fn simulate_sign_send() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    let xdr_base64 = sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--sim-only", "--", "inc"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign")
+        .write_stdin(xdr_base64.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    sandbox
+        .new_assert_cmd("tx")
+        .arg("send")
+        .write_stdin(signed.as_bytes())
+        .assert()
+        .success()
+        .stdout("1\n");
+}
+
+#[tokio::test]
+async fn fee_bump_send() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    sandbox.generate_account("sponsor", None).assert().success();
+    let xdr_base64 = sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--sim-only", "--", "inc"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign")
+        .write_stdin(xdr_base64.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    let fee_bumped = sandbox
+        .new_assert_cmd("tx")
+        .args(["fee-bump", "--fee-source", "sponsor", "--fee", "200"])
+        .write_stdin(signed.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    sandbox
+        .new_assert_cmd("tx")
+        .arg("send")
+        .write_stdin(fee_bumped.as_bytes())
+        .assert()
+        .success()
+        .stdout("1\n");
+}
+
+#[tokio::test]
+async fn invoke_with_fee_source() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    sandbox.generate_account("sponsor", None).assert().success();
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--fee-source", "sponsor", "--", "inc"])
+        .assert()
+        .success()
+        .stdout("1\n");
+}
+
+#[tokio::test]
+async fn expired_transaction_is_rejected() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    let xdr_base64 = sandbox
+        .new_assert_cmd("contract")
+        .args([
+            "invoke",
+            "--id",
+            id,
+            "--sim-only",
+            "--valid-before",
+            "1",
+            "--",
+            "inc",
+        ])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign")
+        .write_stdin(xdr_base64.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    sandbox
+        .new_assert_cmd("tx")
+        .arg("send")
+        .write_stdin(signed.as_bytes())
+        .assert()
+        .failure();
+}
+
+#[tokio::test]
+async fn build_only_with_memo() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    sandbox
+        .new_assert_cmd("contract")
+        .args([
+            "invoke",
+            "--id",
+            id,
+            "--build-only",
+            "--memo-id",
+            "1234",
+            "--",
+            "inc",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Memo: id 1234"));
+    sandbox
+        .new_assert_cmd("contract")
+        .args([
+            "invoke",
+            "--id",
+            id,
+            "--build-only",
+            "--memo-text",
+            "this memo is longer than 28 bytes",
+            "--",
+            "inc",
+        ])
+        .assert()
+        .failure();
+}
+
+#[tokio::test]
+async fn invoke_with_muxed_source() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    sandbox
+        .generate_account("custodian", None)
+        .arg("--mux-id=42")
+        .assert()
+        .success();
+    let muxed = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "custodian"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    assert!(muxed.starts_with('M'));
+    for source in ["custodian", muxed.as_str()] {
+        sandbox
+            .new_assert_cmd("contract")
+            .args(["invoke", "--id", id, "--source", source, "--", "inc"])
+            .assert()
+            .success();
+    }
+}
+
+#[tokio::test]
+async fn concurrent_invokes_with_channel_accounts() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    for channel in ["chan1", "chan2"] {
+        sandbox.generate_account(channel, None).assert().success();
+    }
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                sandbox
+                    .new_assert_cmd("contract")
+                    .args([
+                        "invoke",
+                        "--id",
+                        id,
+                        "--channel-accounts",
+                        "chan1,chan2",
+                        "--",
+                        "inc",
+                    ])
+                    .assert()
+                    .success();
+            });
+        }
+    });
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--is-view", "--", "get_count"])
+        .assert()
+        .success()
+        .stdout("4\n");
+}
+
+#[tokio::test]
+async fn build_only_offline() {
+    let sandbox = &TestEnv::new();
+    let tx_xdr = sandbox
+        .new_assert_cmd("contract")
+        // Nothing listens on this port, so any network request would fail
+        .env("SOROBAN_RPC_URL", "http://localhost:1")
+        .args([
+            "invoke",
+            "--id",
+            DEFAULT_CONTRACT_ID,
+            "--wasm",
+            &HELLO_WORLD.path().to_string_lossy(),
+            "--build-only",
+            "--sequence",
+            "5",
+            "--",
+            "inc",
+        ])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let TransactionEnvelope::Tx(tx_env) =
+        TransactionEnvelope::from_xdr_base64(tx_xdr, Limits::none()).unwrap()
+    else {
+        panic!("expected a transaction envelope");
+    };
+    assert_eq!(tx_env.tx.seq_num.0, 5);
+}
+
+#[tokio::test]
+async fn sep7_only_with_build_or_sim_only() {
+    let sandbox = &TestEnv::new();
+    let invoke = |args: &[&str]| {
+        let mut cmd = sandbox.new_assert_cmd("contract");
+        cmd.env("SOROBAN_RPC_URL", "http://localhost:1")
+            .args([
+                "invoke",
+                "--id",
+                DEFAULT_CONTRACT_ID,
+                "--wasm",
+                &HELLO_WORLD.path().to_string_lossy(),
+                "--sequence",
+                "5",
+                "--sep7",
+            ])
+            .args(args)
+            .args(["--", "inc"]);
+        cmd.assert()
+    };
+    invoke(&["--build-only"])
+        .success()
+        .stdout(predicates::str::starts_with("web+stellar:tx?xdr="));
+    invoke(&[])
+        .failure()
+        .stderr(predicates::str::contains("--build-only|--sim-only"));
+}
+
+#[tokio::test]
+async fn cached_sequence_resyncs_after_bad_seq() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    let invoke = |cache: bool| {
+        let mut cmd = sandbox.new_assert_cmd("contract");
+        cmd.args(["invoke", "--id", id]);
+        if cache {
+            cmd.arg("--cache-sequence");
+        }
+        cmd.args(["--", "inc"]).assert().success();
+    };
+    invoke(true);
+    // Uses up the sequence number the cache expects to be next
+    invoke(false);
+    invoke(true);
+    sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--is-view", "--", "get_count"])
+        .assert()
+        .success()
+        .stdout("3\n");
+}
+
+#[tokio::test]
+async fn export_sign_and_import_auth() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    sandbox.generate_account("signer", None).assert().success();
+    let addr = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "signer"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let auth_file = sandbox.dir().join("auth.txt");
+    let tx_xdr = sandbox
+        .new_assert_cmd("contract")
+        .args(["invoke", "--id", id, "--export-auth"])
+        .arg(&auth_file)
+        .args(["--", "auth", "--addr", &addr, "--world=world"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    // The invoking account holds no key for the address, so it can't send the transaction alone
+    sandbox
+        .new_assert_cmd("contract")
+        .args([
+            "invoke",
+            "--id",
+            id,
+            "--",
+            "auth",
+            "--addr",
+            &addr,
+            "--world=world",
+        ])
+        .assert()
+        .failure();
+    let signed = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign-auth")
+        .arg(&auth_file)
+        .args(["--source", "signer"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed_file = sandbox.dir().join("signed.txt");
+    std::fs::write(&signed_file, signed).unwrap();
+    let tx_xdr = sandbox
+        .new_assert_cmd("tx")
+        .arg("import-auth")
+        .arg("--auth")
+        .arg(&signed_file)
+        .write_stdin(tx_xdr.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed_tx = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign")
+        .write_stdin(tx_xdr.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    sandbox
+        .new_assert_cmd("tx")
+        .arg("send")
+        .write_stdin(signed_tx.as_bytes())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(addr.as_str()));
+}
+
+#[tokio::test]
+async fn preview_and_confirm_auth() {
+    let sandbox = &TestEnv::new();
+    let id = &deploy_hello(sandbox).await;
+    let invoke = |stdin: &str| {
+        sandbox
+            .new_assert_cmd("contract")
+            .args(["invoke", "--id", id, "--confirm"])
+            .args(["--", "auth", "--addr=test", "--world=world"])
+            .write_stdin(stdin)
+            .assert()
+    };
+    invoke("n\n")
+        .failure()
+        .stderr(predicates::str::contains(".auth(addr: \""))
+        .stderr(predicates::str::contains("world: \"world\""))
+        .stderr(predicates::str::contains("not confirmed"));
+    invoke("y\n").success();
+}
+
+#[tokio::test]
+async fn classic_operations() {
+    let sandbox = &TestEnv::new();
+    sandbox.generate_account("issuer", None).assert().success();
+    sandbox
+        .generate_account("holder", None)
+        .arg("--no-fund")
+        .assert()
+        .success();
+    let issuer = sandbox
+        .new_assert_cmd("keys")
+        .args(["address", "issuer"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let usd = format!("USD:{issuer}");
+    let tx_new = |source: &str, args: &[&str]| {
+        sandbox
+            .new_assert_cmd("tx")
+            .arg("new")
+            .args(args)
+            .args(["--source", source])
+            .assert()
+            .success()
+            .stdout(predicates::str::is_match("^[0-9a-f]{64}\n$").unwrap());
+    };
+    tx_new("issuer", &["create-account", "--destination", "holder"]);
+    tx_new("holder", &["change-trust", "--line", &usd]);
+    tx_new(
+        "issuer",
+        &[
+            "payment",
+            "--destination",
+            "holder",
+            "--asset",
+            &usd,
+            "--amount",
+            "100",
+        ],
+    );
+    tx_new(
+        "holder",
+        &[
+            "set-options",
+            "--signer",
+            "issuer",
+            "--signer-weight",
+            "1",
+            "--home-domain",
+            "example.com",
+        ],
+    );
+
+    let tx_xdr = sandbox
+        .new_assert_cmd("tx")
+        .env("SOROBAN_RPC_URL", "http://localhost:1")
+        .args([
+            "new",
+            "payment",
+            "--destination",
+            "holder",
+            "--amount",
+            "5",
+            "--build-only",
+            "--sequence",
+            "9",
+        ])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let TransactionEnvelope::Tx(tx_env) =
+        TransactionEnvelope::from_xdr_base64(tx_xdr, Limits::none()).unwrap()
+    else {
+        panic!("expected a transaction envelope");
+    };
+    assert_eq!(tx_env.tx.seq_num.0, 9);
+    assert_eq!(tx_env.tx.operations.len(), 1);
+}
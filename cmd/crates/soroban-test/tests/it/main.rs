@@ -4,5 +4,7 @@ mod help;
 #[cfg(feature = "it")]
 mod integration;
 mod plugin;
+mod restore;
+mod send;
 mod util;
 mod version;
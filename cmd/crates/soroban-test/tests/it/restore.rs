@@ -0,0 +1,255 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use serde_json::{json, Value};
+use soroban_cli::utils;
+use soroban_env_host::xdr::{
+    ContractDataDurability, ExtensionPoint, Hash, LedgerFootprint, LedgerKey,
+    LedgerKeyContractData, Limits, OperationBody, ReadXdr, ScAddress, ScVal, SorobanResources,
+    SorobanTransactionData, SorobanTransactionMeta, SorobanTransactionMetaExt, TransactionEnvelope,
+    TransactionMeta, TransactionMetaV3, TransactionResult, TransactionResultExt,
+    TransactionResultResult, WriteXdr,
+};
+use soroban_test::{TestEnv, Wasm, LOCAL_NETWORK_PASSPHRASE};
+
+use crate::util::DEFAULT_CONTRACT_ID;
+
+const HELLO_WORLD: &Wasm = &Wasm::Custom("test-wasms", "test_hello_world");
+
+/// Transactions sent to the mock server, whether the archived entry has been restored, and
+/// whether the transactions fail once included in the ledger.
+#[derive(Default)]
+pub(crate) struct Ledger {
+    pub restored: bool,
+    pub failing: bool,
+    pub sent: Vec<&'static str>,
+    /// Hash and XDR of the envelopes sent
+    pub envelopes: Vec<(String, String)>,
+}
+
+/// Serves the RPC methods used to invoke a contract whose storage is archived until a restore
+/// transaction is sent. Returns its url.
+pub(crate) fn mock_rpc(ledger: Arc<Mutex<Ledger>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let ledger = ledger.clone();
+            thread::spawn(move || serve(stream.unwrap(), &ledger));
+        }
+    });
+    url
+}
+
+fn serve(stream: TcpStream, ledger: &Mutex<Ledger>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let result = respond(&request, &mut ledger.lock().unwrap());
+        let body = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    }
+}
+
+fn respond(request: &Value, ledger: &mut Ledger) -> Value {
+    let params = &request["params"];
+    match request["method"].as_str().unwrap() {
+        "getNetwork" => json!({ "passphrase": LOCAL_NETWORK_PASSPHRASE, "protocolVersion": 21 }),
+        "simulateTransaction" => {
+            let mut res = json!({
+                "transactionData": transaction_data(),
+                "minResourceFee": "1000",
+                "results": [{ "auth": [], "xdr": xdr(&ScVal::U32(1)) }],
+                "latestLedger": 10,
+            });
+            if !ledger.restored {
+                res["restorePreamble"] =
+                    json!({ "transactionData": transaction_data(), "minResourceFee": "500" });
+            }
+            res
+        }
+        "sendTransaction" => {
+            let envelope = params["transaction"].as_str().unwrap().to_string();
+            let tx_env = TransactionEnvelope::from_xdr_base64(&envelope, Limits::none()).unwrap();
+            let hash =
+                Hash(utils::transaction_envelope_hash(&tx_env, LOCAL_NETWORK_PASSPHRASE).unwrap())
+                    .to_string();
+            let TransactionEnvelope::Tx(tx_env) = tx_env else {
+                panic!("expected a transaction envelope");
+            };
+            match tx_env.tx.operations[0].body {
+                OperationBody::RestoreFootprint(_) => {
+                    ledger.restored = true;
+                    ledger.sent.push("restore");
+                }
+                OperationBody::InvokeHostFunction(_) => {
+                    assert!(ledger.restored, "invoked before the restore");
+                    ledger.sent.push("invoke");
+                }
+                _ => panic!("unexpected operation"),
+            }
+            ledger.envelopes.push((hash.clone(), envelope));
+            json!({
+                "hash": hash,
+                "status": "PENDING",
+                "latestLedger": 10,
+                "latestLedgerCloseTime": "0",
+            })
+        }
+        "getTransaction" => {
+            let (_, envelope) = ledger
+                .envelopes
+                .iter()
+                .find(|(hash, _)| hash == params["hash"].as_str().unwrap())
+                .unwrap();
+            let result = TransactionResult {
+                fee_charged: 1000,
+                result: if ledger.failing {
+                    TransactionResultResult::TxFailed(vec![].try_into().unwrap())
+                } else {
+                    TransactionResultResult::TxSuccess(vec![].try_into().unwrap())
+                },
+                ext: TransactionResultExt::V0,
+            };
+            let meta = TransactionMeta::V3(TransactionMetaV3 {
+                ext: ExtensionPoint::V0,
+                tx_changes_before: vec![].try_into().unwrap(),
+                operations: vec![].try_into().unwrap(),
+                tx_changes_after: vec![].try_into().unwrap(),
+                soroban_meta: Some(SorobanTransactionMeta {
+                    ext: SorobanTransactionMetaExt::V0,
+                    events: vec![].try_into().unwrap(),
+                    return_value: ScVal::U32(1),
+                    diagnostic_events: vec![].try_into().unwrap(),
+                }),
+            });
+            json!({
+                "status": if ledger.failing { "FAILED" } else { "SUCCESS" },
+                "envelopeXdr": envelope,
+                "resultXdr": xdr(&result),
+                "resultMetaXdr": xdr(&meta),
+            })
+        }
+        method => panic!("unexpected method {method}"),
+    }
+}
+
+/// Footprint writing the counter of the contract, which is the archived entry.
+fn transaction_data() -> String {
+    let contract_id = stellar_strkey::Contract::from_string(DEFAULT_CONTRACT_ID).unwrap();
+    xdr(&SorobanTransactionData {
+        ext: ExtensionPoint::V0,
+        resources: SorobanResources {
+            footprint: LedgerFootprint {
+                read_only: vec![].try_into().unwrap(),
+                read_write: vec![LedgerKey::ContractData(LedgerKeyContractData {
+                    contract: ScAddress::Contract(Hash(contract_id.0)),
+                    key: ScVal::Symbol("COUNTER".try_into().unwrap()),
+                    durability: ContractDataDurability::Persistent,
+                })]
+                .try_into()
+                .unwrap(),
+            },
+            instructions: 1000,
+            read_bytes: 100,
+            write_bytes: 100,
+        },
+        resource_fee: 1000,
+    })
+}
+
+fn xdr(value: &impl WriteXdr) -> String {
+    value.to_xdr_base64(Limits::none()).unwrap()
+}
+
+/// Test environment using the mock server, with an unfunded test account.
+pub(crate) fn sandbox(ledger: &Arc<Mutex<Ledger>>) -> TestEnv {
+    let sandbox = TestEnv {
+        rpc_url: mock_rpc(ledger.clone()),
+        ..Default::default()
+    };
+    sandbox
+        .generate_account("test", None)
+        .arg("--no-fund")
+        .assert()
+        .success();
+    sandbox
+}
+
+pub(crate) fn invoke(sandbox: &TestEnv, args: &[&str]) -> assert_cmd::Command {
+    let mut cmd = sandbox.new_assert_cmd("contract");
+    cmd.args([
+        "invoke",
+        "--id",
+        DEFAULT_CONTRACT_ID,
+        "--wasm",
+        &HELLO_WORLD.path().to_string_lossy(),
+        "--sequence",
+        "5",
+    ])
+    .args(args)
+    .args(["--", "inc"]);
+    cmd
+}
+
+#[test]
+fn archived_entries_are_restored_before_invoking() {
+    let ledger = Arc::new(Mutex::new(Ledger::default()));
+    let sandbox = &sandbox(&ledger);
+    invoke(sandbox, &["--auto-restore"])
+        .assert()
+        .success()
+        .stdout("1\n");
+    assert_eq!(ledger.lock().unwrap().sent, ["restore", "invoke"]);
+}
+
+#[test]
+fn archived_entries_fail_without_auto_restore() {
+    let ledger = Arc::new(Mutex::new(Ledger::default()));
+    let sandbox = &sandbox(&ledger);
+    invoke(sandbox, &[])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("archived"))
+        .stderr(predicates::str::contains("--auto-restore"));
+    assert!(ledger.lock().unwrap().sent.is_empty());
+}
+
+#[test]
+fn fee_caps_are_checked_before_restoring() {
+    let ledger = Arc::new(Mutex::new(Ledger::default()));
+    let sandbox = &sandbox(&ledger);
+    invoke(sandbox, &["--auto-restore", "--max-total-fee", "1000"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--max-total-fee"));
+    assert!(ledger.lock().unwrap().sent.is_empty());
+}
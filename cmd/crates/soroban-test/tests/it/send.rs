@@ -0,0 +1,35 @@
+use std::sync::{Arc, Mutex};
+
+use soroban_test::AssertExt;
+
+use crate::restore::{invoke, sandbox, Ledger};
+
+#[test]
+fn failed_transaction_reports_its_result() {
+    let ledger = Arc::new(Mutex::new(Ledger {
+        restored: true,
+        failing: true,
+        ..Default::default()
+    }));
+    let sandbox = &sandbox(&ledger);
+    let tx_xdr = invoke(sandbox, &["--build-only"])
+        .assert()
+        .success()
+        .stdout_as_str();
+    let signed = sandbox
+        .new_assert_cmd("tx")
+        .arg("sign")
+        .write_stdin(tx_xdr.as_bytes())
+        .assert()
+        .success()
+        .stdout_as_str();
+    sandbox
+        .new_assert_cmd("tx")
+        .arg("send")
+        .write_stdin(signed.as_bytes())
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicates::str::contains("failed with result TxFailed"));
+    assert_eq!(ledger.lock().unwrap().sent, ["invoke"]);
+}
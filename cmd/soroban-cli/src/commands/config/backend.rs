@@ -0,0 +1,413 @@
+use std::{fmt::Display, str::FromStr};
+
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+
+use crate::{signer::Signer, utils};
+
+use super::{
+    encryption,
+    secret::{self, Error, Secret},
+};
+
+/// Kind of backend keeping the secret of an identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    PlaintextFile,
+    EncryptedFile,
+    EnvVar,
+    ExternalCommand,
+    WatchOnly,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Kind::PlaintextFile => "file",
+                Kind::EncryptedFile => "encrypted file",
+                Kind::EnvVar => "env",
+                Kind::ExternalCommand => "command",
+                Kind::WatchOnly => "public key",
+            }
+        )
+    }
+}
+
+/// Where the secret of an identity is kept, and how its keys are obtained from it. The identity
+/// file only holds what the backend needs to find the secret.
+pub trait Backend {
+    fn kind(&self) -> Kind;
+
+    /// Private key at `index` of the hd path, for secrets that are seed phrases.
+    fn private_key(&self, index: Option<usize>) -> Result<PrivateKey, Error>;
+
+    fn public_key(&self, index: Option<usize>) -> Result<PublicKey, Error> {
+        Ok(self.signer(index)?.public_key())
+    }
+
+    /// Public key, when it is known without running a process or prompting for a passphrase,
+    /// e.g. to look the identity up by its address.
+    fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey>;
+
+    /// Signer of the transactions of the identity.
+    fn signer(&self, index: Option<usize>) -> Result<Signer, Error> {
+        Ok(Signer::Local(utils::into_signing_key(
+            &self.private_key(index)?,
+        )))
+    }
+}
+
+/// Secret key or seed phrase written as is in the identity file. A seed phrase can have a
+/// mnemonic passphrase, which is not stored: only the public key of its first hd path is, to
+/// look the identity up and to check the passphrase entered.
+pub enum PlaintextFile<'a> {
+    SecretKey(&'a str),
+    SeedPhrase(&'a str),
+    SeedPhraseWithPassphrase {
+        seed_phrase: &'a str,
+        public_key: &'a str,
+    },
+}
+
+impl Backend for PlaintextFile<'_> {
+    fn kind(&self) -> Kind {
+        Kind::PlaintextFile
+    }
+
+    fn private_key(&self, index: Option<usize>) -> Result<PrivateKey, Error> {
+        Ok(match self {
+            PlaintextFile::SecretKey(secret_key) => PrivateKey::from_string(secret_key)?,
+            PlaintextFile::SeedPhrase(seed_phrase) => PrivateKey::from_payload(
+                &sep5::SeedPhrase::from_str(seed_phrase)?
+                    .from_path_index(index.unwrap_or_default(), None)?
+                    .private()
+                    .0,
+            )?,
+            PlaintextFile::SeedPhraseWithPassphrase {
+                seed_phrase,
+                public_key,
+            } => seed_phrase_private_key(
+                seed_phrase,
+                public_key,
+                &secret::mnemonic_passphrase()?,
+                index,
+            )?,
+        })
+    }
+
+    fn public_key(&self, index: Option<usize>) -> Result<PublicKey, Error> {
+        match self {
+            PlaintextFile::SeedPhraseWithPassphrase { public_key, .. }
+                if index.unwrap_or_default() == 0 =>
+            {
+                Ok(PublicKey::from_string(public_key)?)
+            }
+            _ => Ok(self.signer(index)?.public_key()),
+        }
+    }
+
+    fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey> {
+        match self {
+            PlaintextFile::SeedPhraseWithPassphrase { public_key, .. } => {
+                (index.unwrap_or_default() == 0).then(|| PublicKey::from_string(public_key).ok())?
+            }
+            _ => self.public_key(index).ok(),
+        }
+    }
+}
+
+/// Private key at `index` of a seed phrase with a mnemonic passphrase, once the passphrase is
+/// checked against the public key of the first hd path.
+fn seed_phrase_private_key(
+    seed_phrase: &str,
+    public_key: &str,
+    passphrase: &str,
+    index: Option<usize>,
+) -> Result<PrivateKey, Error> {
+    let seed_phrase = sep5::SeedPhrase::from_str(seed_phrase)?;
+    if seed_phrase
+        .from_path_index(0, Some(passphrase))?
+        .public()
+        .to_string()
+        != public_key
+    {
+        return Err(Error::WrongMnemonicPassphrase(public_key.to_string()));
+    }
+    Ok(PrivateKey::from_payload(
+        &seed_phrase
+            .from_path_index(index.unwrap_or_default(), Some(passphrase))?
+            .private()
+            .0,
+    )?)
+}
+
+/// Secret key or seed phrase encrypted with the identity passphrase, followed on a new line by
+/// the mnemonic passphrase of the seed phrase if it has one. The public key of its first hd path
+/// is kept in the clear, so that the identity can be looked up without decrypting it.
+pub struct EncryptedFile<'a> {
+    pub public_key: &'a str,
+    pub encrypted: encryption::Encrypted,
+}
+
+impl EncryptedFile<'_> {
+    /// Private key at `index` of the `plaintext` secret.
+    fn private_key_of(&self, plaintext: &str, index: Option<usize>) -> Result<PrivateKey, Error> {
+        match plaintext.split_once('\n') {
+            Some((seed_phrase, passphrase)) => {
+                seed_phrase_private_key(seed_phrase, self.public_key, passphrase, index)
+            }
+            None => plaintext.parse::<Secret>()?.private_key(index),
+        }
+    }
+}
+
+impl Backend for EncryptedFile<'_> {
+    fn kind(&self) -> Kind {
+        Kind::EncryptedFile
+    }
+
+    fn private_key(&self, index: Option<usize>) -> Result<PrivateKey, Error> {
+        let plaintext = encryption::decrypt(&self.encrypted, &encryption::passphrase(false)?)?;
+        self.private_key_of(&plaintext, index)
+    }
+
+    fn public_key(&self, index: Option<usize>) -> Result<PublicKey, Error> {
+        if index.unwrap_or_default() == 0 {
+            Ok(PublicKey::from_string(self.public_key)?)
+        } else {
+            Ok(self.signer(index)?.public_key())
+        }
+    }
+
+    fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey> {
+        (index.unwrap_or_default() == 0).then(|| PublicKey::from_string(self.public_key).ok())?
+    }
+}
+
+/// Secret key or seed phrase read from an environment variable whenever the identity is used,
+/// e.g. one injected by CI.
+pub struct EnvVar<'a> {
+    pub name: &'a str,
+}
+
+impl EnvVar<'_> {
+    fn secret(&self) -> Result<Secret, Error> {
+        self.parse(std::env::var(self.name).ok())
+    }
+
+    /// Secret held by the variable, given its `value` if it is set.
+    fn parse(&self, value: Option<String>) -> Result<Secret, Error> {
+        let value = value.ok_or_else(|| Error::MissingEnvVar(self.name.to_string()))?;
+        match value.parse()? {
+            secret @ (Secret::SecretKey { .. } | Secret::SeedPhrase { .. }) => Ok(secret),
+            _ => Err(Error::InvalidEnvVar(self.name.to_string())),
+        }
+    }
+}
+
+impl Backend for EnvVar<'_> {
+    fn kind(&self) -> Kind {
+        Kind::EnvVar
+    }
+
+    fn private_key(&self, index: Option<usize>) -> Result<PrivateKey, Error> {
+        self.secret()?.private_key(index)
+    }
+
+    fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey> {
+        self.public_key(index).ok()
+    }
+}
+
+/// External command signing for the identity, which never releases its secret. Its public key
+/// is recorded when the identity is added, so that the command is only run to sign.
+pub struct ExternalCommand<'a> {
+    pub signer: &'a str,
+    pub public_key: Option<&'a str>,
+}
+
+impl Backend for ExternalCommand<'_> {
+    fn kind(&self) -> Kind {
+        Kind::ExternalCommand
+    }
+
+    fn private_key(&self, _: Option<usize>) -> Result<PrivateKey, Error> {
+        Err(Error::ExternalSigner(self.signer.to_string()))
+    }
+
+    fn public_key(&self, index: Option<usize>) -> Result<PublicKey, Error> {
+        if let Some(public_key) = self.public_key {
+            Ok(PublicKey::from_string(public_key)?)
+        } else {
+            Ok(self.signer(index)?.public_key())
+        }
+    }
+
+    fn known_public_key(&self, _: Option<usize>) -> Option<PublicKey> {
+        PublicKey::from_string(self.public_key?).ok()
+    }
+
+    fn signer(&self, _: Option<usize>) -> Result<Signer, Error> {
+        Ok(Signer::External(crate::signer::External::new(self.signer)?))
+    }
+}
+
+/// Public key of an account known by name, such as a partner's or a co-signer's, with no secret
+/// to sign for it.
+pub struct WatchOnly<'a> {
+    pub public_key: &'a str,
+}
+
+impl Backend for WatchOnly<'_> {
+    fn kind(&self) -> Kind {
+        Kind::WatchOnly
+    }
+
+    fn private_key(&self, _: Option<usize>) -> Result<PrivateKey, Error> {
+        Err(Error::WatchOnly(self.public_key.to_string()))
+    }
+
+    fn public_key(&self, _: Option<usize>) -> Result<PublicKey, Error> {
+        Ok(PublicKey::from_string(self.public_key)?)
+    }
+
+    fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey> {
+        self.public_key(index).ok()
+    }
+
+    fn signer(&self, _: Option<usize>) -> Result<Signer, Error> {
+        Err(Error::WatchOnly(self.public_key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var() {
+        let secret_key = "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD";
+        let secret: Secret = "env:CI_SECRET_KEY".parse().unwrap();
+        assert_eq!(secret.backend().kind(), Kind::EnvVar);
+        let env_var = EnvVar {
+            name: "CI_SECRET_KEY",
+        };
+        let parsed = env_var.parse(Some(secret_key.to_string())).unwrap();
+        assert_eq!(
+            parsed.private_key(None).unwrap(),
+            PrivateKey::from_string(secret_key).unwrap()
+        );
+        assert_eq!(
+            parsed.public_key(None).unwrap(),
+            Secret::from_str(secret_key)
+                .unwrap()
+                .public_key(None)
+                .unwrap()
+        );
+        assert!(matches!(env_var.parse(None), Err(Error::MissingEnvVar(_))));
+        assert!(matches!(
+            env_var.parse(Some("cmd:false".to_string())),
+            Err(Error::InvalidEnvVar(_))
+        ));
+    }
+
+    #[test]
+    fn test_known_public_key() {
+        let address = "GABISKK4K5VQK3XZKVQMU3TBAB7QCVES4NYLNQYBA73M7VDO4UXEMGFX";
+        let public_key = PublicKey::from_string(address).unwrap();
+        // The signer command does not exist, so it is never run
+        let external = Secret::ExternalSigner {
+            signer: "cmd:/nonexistent/signer".to_string(),
+            public_key: Some(address.to_string()),
+        };
+        assert_eq!(external.known_public_key(None), Some(public_key));
+        assert_eq!(external.public_key(None).unwrap(), public_key);
+        let external = Secret::ExternalSigner {
+            signer: "cmd:/nonexistent/signer".to_string(),
+            public_key: None,
+        };
+        assert_eq!(external.known_public_key(None), None);
+        let encrypted = Secret::Encrypted {
+            public_key: address.to_string(),
+            salt: String::new(),
+            nonce: String::new(),
+            encrypted_secret: String::new(),
+        };
+        assert_eq!(encrypted.known_public_key(None), Some(public_key));
+        assert_eq!(encrypted.known_public_key(Some(1)), None);
+    }
+
+    #[test]
+    fn test_seed_phrase_with_passphrase() {
+        let seed_phrase = Secret::test_seed_phrase().unwrap();
+        let Secret::SeedPhrase { seed_phrase } = seed_phrase else {
+            panic!("expected a seed phrase");
+        };
+        let public_key = sep5::SeedPhrase::from_str(&seed_phrase)
+            .unwrap()
+            .from_path_index(0, Some("correct horse"))
+            .unwrap()
+            .public();
+        let secret = Secret::SeedPhraseWithPassphrase {
+            public_key: public_key.to_string(),
+            seed_phrase: seed_phrase.clone(),
+        };
+        assert_ne!(
+            Secret::SeedPhrase {
+                seed_phrase: seed_phrase.clone()
+            }
+            .public_key(None)
+            .unwrap(),
+            public_key
+        );
+        assert_eq!(secret.public_key(None).unwrap(), public_key);
+        let address = public_key.to_string();
+        let private_key =
+            seed_phrase_private_key(&seed_phrase, &address, "correct horse", None).unwrap();
+        assert_eq!(
+            Signer::Local(utils::into_signing_key(&private_key)).public_key(),
+            public_key
+        );
+        assert!(matches!(
+            seed_phrase_private_key(&seed_phrase, &address, "battery staple", Some(1)),
+            Err(Error::WrongMnemonicPassphrase(_))
+        ));
+    }
+
+    #[test]
+    fn test_encrypted_seed_phrase_with_passphrase() {
+        let Secret::SeedPhrase { seed_phrase } = Secret::test_seed_phrase().unwrap() else {
+            panic!("expected a seed phrase");
+        };
+        let public_key = sep5::SeedPhrase::from_str(&seed_phrase)
+            .unwrap()
+            .from_path_index(0, Some("correct horse"))
+            .unwrap()
+            .public()
+            .to_string();
+        let encrypted = EncryptedFile {
+            public_key: &public_key,
+            encrypted: encryption::encrypt("", "").unwrap(),
+        };
+        let plaintext = format!("{seed_phrase}\ncorrect horse");
+        assert_eq!(
+            Signer::Local(utils::into_signing_key(
+                &encrypted.private_key_of(&plaintext, None).unwrap()
+            ))
+            .public_key()
+            .to_string(),
+            public_key
+        );
+        assert_ne!(
+            encrypted.private_key_of(&plaintext, Some(1)).unwrap(),
+            encrypted.private_key_of(&plaintext, None).unwrap()
+        );
+        assert!(matches!(
+            encrypted.private_key_of(&format!("{seed_phrase}\nbattery staple"), None),
+            Err(Error::WrongMnemonicPassphrase(_))
+        ));
+        assert!(encrypted.private_key_of(&seed_phrase, None).is_ok());
+    }
+}
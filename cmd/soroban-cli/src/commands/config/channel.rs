@@ -0,0 +1,110 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use fs2::FileExt;
+use rand::Rng;
+
+use super::{data, secret};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] Box<super::Error>),
+    #[error(transparent)]
+    Secret(#[from] secret::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error("Failed to lock channel account {path:?}: {error}")]
+    Lock { path: PathBuf, error: io::Error },
+    #[error("No channel account became free within {0:?}, all are in use by other processes")]
+    NoneAvailable(Duration),
+}
+
+/// How long to wait for a channel account to become free.
+const WAIT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Exclusive use of a channel account by this process, with an advisory lock on its lock file.
+/// The lock is released when dropped, and by the OS if the process exits without dropping it.
+#[derive(Debug)]
+pub struct Lock {
+    file: fs::File,
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Lock one of the channel accounts of `pool`, starting from a random one so that concurrent
+/// processes spread across the pool. Returns the config with the channel account as source.
+pub async fn acquire(pool: &[String], config: &super::Args) -> Result<(super::Args, Lock), Error> {
+    let dir = data::channel_locks_dir()?;
+    let channels = pool
+        .iter()
+        .map(|name| {
+            let public_key = config.account(name).map_err(Box::new)?.public_key(None)?;
+            Ok((
+                name,
+                dir.join(public_key.to_string()).with_extension("lock"),
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let start = Instant::now();
+    let offset = rand::thread_rng().gen_range(0..channels.len().max(1));
+    loop {
+        for (name, path) in channels.iter().cycle().skip(offset).take(channels.len()) {
+            if let Some(lock) = try_lock(path)? {
+                tracing::debug!("using channel account {name}");
+                let channel = super::Args {
+                    source_account: (*name).clone(),
+                    hd_path: None,
+                    ..config.clone()
+                };
+                return Ok((channel, lock));
+            }
+        }
+        if start.elapsed() > WAIT {
+            return Err(Error::NoneAvailable(WAIT));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn try_lock(path: &PathBuf) -> Result<Option<Lock>, Error> {
+    let lock_error = |error| Error::Lock {
+        path: path.clone(),
+        error,
+    };
+    // The file is left in place, so that every process locks the same one
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(lock_error)?;
+    match file.try_lock_exclusive() {
+        Ok(()) => Ok(Some(Lock { file })),
+        Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(None),
+        Err(error) => Err(lock_error(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("channel.lock");
+        let lock = try_lock(&path).unwrap().unwrap();
+        assert!(try_lock(&path).unwrap().is_none());
+        drop(lock);
+        assert!(try_lock(&path).unwrap().is_some());
+    }
+}
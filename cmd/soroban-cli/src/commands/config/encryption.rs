@@ -0,0 +1,133 @@
+use std::sync::OnceLock;
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chacha20poly1305::{aead::Aead, KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+/// Environment variable holding the passphrase of encrypted identities, read instead of
+/// prompting for it.
+pub const PASSPHRASE_ENV: &str = "STELLAR_IDENTITY_PASSPHRASE";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("reading the identity passphrase")]
+    PassphraseRead,
+    #[error("the passphrases do not match")]
+    PassphraseMismatch,
+    #[error("the identity passphrase cannot be empty")]
+    EmptyPassphrase,
+    #[error("deriving the key from the passphrase: {0}")]
+    Kdf(argon2::Error),
+    #[error("cannot decrypt the identity: wrong passphrase or corrupted file")]
+    Decryption,
+    #[error("invalid encrypted identity: {0}")]
+    Invalid(&'static str),
+}
+
+/// Secret encrypted with XChaCha20-Poly1305, under a key derived from a passphrase and `salt`
+/// with Argon2id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encrypted {
+    /// Hex encoded salt of the key derivation
+    pub salt: String,
+    /// Hex encoded nonce of the encryption
+    pub nonce: String,
+    /// Base64 encoded ciphertext
+    pub ciphertext: String,
+}
+
+pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<Encrypted, Error> {
+    let mut salt = [0; 16];
+    let mut nonce = [0; 24];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| Error::Invalid("encryption failed"))?;
+    Ok(Encrypted {
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+pub fn decrypt(encrypted: &Encrypted, passphrase: &str) -> Result<String, Error> {
+    let salt = hex::decode(&encrypted.salt).map_err(|_| Error::Invalid("salt"))?;
+    let nonce: [u8; 24] = hex::decode(&encrypted.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or(Error::Invalid("nonce"))?;
+    let ciphertext = BASE64
+        .decode(&encrypted.ciphertext)
+        .map_err(|_| Error::Invalid("ciphertext"))?;
+    let plaintext = cipher(passphrase, &salt)?
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| Error::Decryption)?;
+    String::from_utf8(plaintext).map_err(|_| Error::Decryption)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, Error> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(Error::Kdf)?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// Passphrase entered once per run, reused for every identity decrypted.
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// Passphrase of encrypted identities, from `STELLAR_IDENTITY_PASSPHRASE` or else prompted for.
+/// When encrypting, a prompted passphrase has to be typed twice.
+pub fn passphrase(confirm: bool) -> Result<String, Error> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return non_empty(passphrase);
+    }
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    }
+    let passphrase = non_empty(prompt("Identity passphrase: ")?)?;
+    if confirm && prompt("Confirm the passphrase: ")? != passphrase {
+        return Err(Error::PassphraseMismatch);
+    }
+    Ok(PASSPHRASE.get_or_init(|| passphrase).clone())
+}
+
+fn prompt(prompt: &str) -> Result<String, Error> {
+    rpassword::prompt_password(prompt).map_err(|_| Error::PassphraseRead)
+}
+
+fn non_empty(passphrase: String) -> Result<String, Error> {
+    if passphrase.is_empty() {
+        Err(Error::EmptyPassphrase)
+    } else {
+        Ok(passphrase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let secret = "SDIY6AQQ75WMD4W46EYB7O6UYMHOCGQHLAQGQTKHDX4J2DYQCHVCQYFD";
+        let encrypted = encrypt(secret, "correct horse").unwrap();
+        assert!(!encrypted.ciphertext.contains(secret));
+        assert_ne!(encrypted, encrypt(secret, "correct horse").unwrap());
+        assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), secret);
+        assert!(matches!(
+            decrypt(&encrypted, "battery staple"),
+            Err(Error::Decryption)
+        ));
+        let tampered = Encrypted {
+            nonce: "00".to_string(),
+            ..encrypted
+        };
+        assert!(matches!(
+            decrypt(&tampered, "correct horse"),
+            Err(Error::Invalid("nonce"))
+        ));
+    }
+}
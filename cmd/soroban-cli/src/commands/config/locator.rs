@@ -1,6 +1,7 @@
 use clap::arg;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
     fmt::Display,
     fs, io,
@@ -8,7 +9,10 @@ use std::{
     str::FromStr,
 };
 
-use crate::{utils::find_config_dir, Pwd};
+use crate::{
+    utils::{self, find_config_dir},
+    Pwd,
+};
 
 use super::{network::Network, secret::Secret};
 
@@ -60,6 +64,37 @@ pub enum Error {
     String(#[from] std::string::FromUtf8Error),
     #[error(transparent)]
     Secret(#[from] crate::commands::config::secret::Error),
+    #[error("No identity found for {0}")]
+    IdentityNotFound(String),
+    #[error("Contract alias {0:?} can only contain letters, digits, `_` and `-`")]
+    InvalidContractAlias(String),
+    #[error("Contract alias {0:?} would be read as a contract ID")]
+    ContractAliasIsContractId(String),
+    #[error("Invalid contract ID {0:?}")]
+    InvalidContractId(String),
+    #[error("{0:?} is neither a contract ID nor an alias of a contract on this network. Aliases are added with `contract alias add`")]
+    ContractNotFound(String),
+}
+
+/// Contract IDs of an alias, keyed by the passphrase of the network the contract is on.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ContractIds {
+    pub ids: BTreeMap<String, String>,
+}
+
+/// Identity file contents. An identity with a mux id is a muxed sub-account of the account of
+/// its secret.
+#[derive(Serialize)]
+struct IdentityFile<'a> {
+    #[serde(flatten)]
+    secret: &'a Secret,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mux_id: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MuxId {
+    mux_id: Option<u64>,
 }
 
 #[derive(Debug, clap::Args, Default, Clone)]
@@ -140,7 +175,16 @@ impl Args {
     }
 
     pub fn write_identity(&self, name: &str, secret: &Secret) -> Result<(), Error> {
-        KeyType::Identity.write(name, secret, &self.config_dir()?)
+        self.write_muxed_identity(name, secret, None)
+    }
+
+    pub fn write_muxed_identity(
+        &self,
+        name: &str,
+        secret: &Secret,
+        mux_id: Option<u64>,
+    ) -> Result<(), Error> {
+        KeyType::Identity.write(name, &IdentityFile { secret, mux_id }, &self.config_dir()?)
     }
 
     pub fn write_network(&self, name: &str, network: &Network) -> Result<(), Error> {
@@ -196,6 +240,69 @@ impl Args {
         KeyType::Identity.read_with_global(name, &self.local_config()?)
     }
 
+    pub fn read_identity_mux_id(&self, name: &str) -> Result<Option<u64>, Error> {
+        Ok(KeyType::Identity
+            .read_with_global::<MuxId>(name, &self.local_config()?)?
+            .mux_id)
+    }
+
+    /// Encrypt with `passphrase` the secrets of the identities `names`, or of all the local and
+    /// global identities if there are none, keeping their mux ids. Only identities with a
+    /// plaintext secret are encrypted, the others are left as they are. Returns the names of the
+    /// identities encrypted.
+    pub fn encrypt_identities(
+        &self,
+        names: &[String],
+        passphrase: &str,
+    ) -> Result<Vec<String>, Error> {
+        let identities = KeyType::Identity.list_paths(&self.local_and_global()?)?;
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !identities.iter().any(|(n, _)| n == *name))
+        {
+            return Err(Error::ConfigMissing(
+                KeyType::Identity.to_string(),
+                missing.clone(),
+            ));
+        }
+        let mut encrypted = Vec::new();
+        for (name, location) in identities {
+            if !names.is_empty() && !names.contains(&name) {
+                continue;
+            }
+            let path = location.as_ref();
+            let secret = KeyType::read_from_path::<Secret>(path)?;
+            if !secret.is_encryptable() {
+                continue;
+            }
+            let mux_id = KeyType::read_from_path::<MuxId>(path)?.mux_id;
+            let secret = secret.encrypt(passphrase)?;
+            KeyType::write_to_path(
+                path,
+                &IdentityFile {
+                    secret: &secret,
+                    mux_id,
+                },
+            )?;
+            encrypted.push(name);
+        }
+        Ok(encrypted)
+    }
+
+    /// Find the identity whose secret has the public key `address`. Only public keys known
+    /// without running an external signer or prompting for a passphrase are compared.
+    pub fn identity_for_public_key(
+        &self,
+        address: &stellar_strkey::ed25519::PublicKey,
+        hd_path: Option<usize>,
+    ) -> Result<Secret, Error> {
+        self.list_identities()?
+            .iter()
+            .filter_map(|name| self.read_identity(name).ok())
+            .find(|secret| secret.known_public_key(hd_path) == Some(*address))
+            .ok_or_else(|| Error::IdentityNotFound(address.to_string()))
+    }
+
     pub fn read_network(&self, name: &str) -> Result<Network, Error> {
         let res = KeyType::Network.read_with_global(name, &self.local_config()?);
         if let Err(Error::ConfigMissing(_, _)) = &res {
@@ -208,6 +315,86 @@ impl Args {
         res
     }
 
+    /// Record `contract_id` as the contract of `alias` on the network of `network_passphrase`,
+    /// replacing any it had there.
+    pub fn save_contract_id(
+        &self,
+        network_passphrase: &str,
+        contract_id: &str,
+        alias: &str,
+    ) -> Result<(), Error> {
+        validate_contract_alias(alias)?;
+        let contract_id = stellar_strkey::Contract(
+            utils::contract_id_from_str(contract_id)
+                .map_err(|_| Error::InvalidContractId(contract_id.to_string()))?,
+        );
+        let pwd = self.config_dir()?;
+        let mut contract_ids = KeyType::ContractIds
+            .read::<ContractIds>(alias, &pwd)
+            .unwrap_or_default();
+        contract_ids
+            .ids
+            .insert(network_passphrase.to_string(), contract_id.to_string());
+        KeyType::ContractIds.write(alias, &contract_ids, &pwd)
+    }
+
+    /// Remove the contract of `alias` on the network of `network_passphrase`.
+    pub fn remove_contract_id(&self, network_passphrase: &str, alias: &str) -> Result<(), Error> {
+        let pwd = self.config_dir()?;
+        let mut contract_ids = KeyType::ContractIds.read::<ContractIds>(alias, &pwd)?;
+        if contract_ids.ids.remove(network_passphrase).is_none() {
+            return Err(Error::ContractNotFound(alias.to_string()));
+        }
+        if contract_ids.ids.is_empty() {
+            KeyType::ContractIds.remove(alias, &pwd)
+        } else {
+            KeyType::ContractIds.write(alias, &contract_ids, &pwd)
+        }
+    }
+
+    /// Contract of `alias` on the network of `network_passphrase`, if it has one there.
+    pub fn get_contract_id(
+        &self,
+        alias: &str,
+        network_passphrase: &str,
+    ) -> Result<Option<String>, Error> {
+        match KeyType::ContractIds.read_with_global::<ContractIds>(alias, &self.local_config()?) {
+            Ok(contract_ids) => Ok(contract_ids.ids.get(network_passphrase).cloned()),
+            Err(Error::ConfigMissing(_, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Local and global contract aliases, with their contracts on every network.
+    pub fn list_contract_aliases(&self) -> Result<Vec<(String, ContractIds)>, Error> {
+        Ok(KeyType::ContractIds
+            .list_paths(&self.local_and_global()?)?
+            .into_iter()
+            .filter_map(|(alias, location)| {
+                Some((alias, KeyType::read_from_path(location.as_ref()).ok()?))
+            })
+            .collect())
+    }
+
+    /// Contract ID given as is, or as an alias of a contract on the network of
+    /// `network_passphrase`. Aliases take precedence over the short hex contract IDs still
+    /// accepted for backwards compatibility.
+    pub fn resolve_contract_id(
+        &self,
+        alias_or_id: &str,
+        network_passphrase: &str,
+    ) -> Result<[u8; 32], Error> {
+        if let Some(contract_id) = utils::exact_contract_id_from_str(alias_or_id) {
+            return Ok(contract_id);
+        }
+        if let Some(contract_id) = self.get_contract_id(alias_or_id, network_passphrase)? {
+            return utils::contract_id_from_str(&contract_id)
+                .map_err(|_| Error::InvalidContractId(contract_id));
+        }
+        utils::contract_id_from_str(alias_or_id)
+            .map_err(|_| Error::ContractNotFound(alias_or_id.to_string()))
+    }
+
     pub fn remove_identity(&self, name: &str) -> Result<(), Error> {
         KeyType::Identity.remove(name, &self.config_dir()?)
     }
@@ -247,6 +434,7 @@ fn read_dir(dir: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
 pub enum KeyType {
     Identity,
     Network,
+    ContractIds,
 }
 
 impl Display for KeyType {
@@ -257,6 +445,7 @@ impl Display for KeyType {
             match self {
                 KeyType::Identity => "identity",
                 KeyType::Network => "network",
+                KeyType::ContractIds => "contract-ids",
             }
         )
     }
@@ -293,8 +482,15 @@ impl KeyType {
         pwd: &Path,
     ) -> Result<(), Error> {
         let filepath = ensure_directory(self.path(pwd, key))?;
+        Self::write_to_path(&filepath, value)
+    }
+
+    pub fn write_to_path<T: serde::Serialize>(path: &Path, value: &T) -> Result<(), Error> {
         let data = toml::to_string(value).map_err(|_| Error::ConfigSerialization)?;
-        std::fs::write(&filepath, data).map_err(|error| Error::IdCreationFailed { filepath, error })
+        std::fs::write(path, data).map_err(|error| Error::IdCreationFailed {
+            filepath: path.to_path_buf(),
+            error,
+        })
     }
 
     fn root(&self, pwd: &Path) -> PathBuf {
@@ -340,6 +536,23 @@ impl KeyType {
     }
 }
 
+/// Check that `alias` can name a contract, and be used as the file name of its contract IDs.
+/// Aliases that parse as a contract ID, such as short hex strings, are rejected so that they
+/// are never mistaken for one.
+pub fn validate_contract_alias(alias: &str) -> Result<(), Error> {
+    if alias.is_empty()
+        || !alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(Error::InvalidContractAlias(alias.to_string()));
+    }
+    if utils::contract_id_from_str(alias).is_ok() {
+        return Err(Error::ContractAliasIsContractId(alias.to_string()));
+    }
+    Ok(())
+}
+
 fn global_config_path() -> Result<PathBuf, Error> {
     Ok(if let Ok(config_home) = std::env::var("XDG_CONFIG_HOME") {
         PathBuf::from_str(&config_home).map_err(|_| Error::XdgConfigHome(config_home))?
@@ -356,3 +569,55 @@ impl Pwd for Args {
         self.config_dir = Some(pwd.to_path_buf());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT_ID: &str = "CAS3J7GYLGXMF6TDJBBYYSE3HQ6BBSMLNUQ34T6TZMYMW2EVH34XOWMA";
+
+    #[test]
+    fn contract_alias_cannot_be_a_contract_id() {
+        for alias in ["cafe", "42", CONTRACT_ID] {
+            assert!(matches!(
+                validate_contract_alias(alias),
+                Err(Error::ContractAliasIsContractId(_))
+            ));
+        }
+        assert!(validate_contract_alias("dead-beef").is_ok());
+    }
+
+    #[test]
+    fn hex_looking_contract_alias_resolves_to_its_contract() {
+        let dir = tempfile::tempdir().unwrap();
+        let locator = Args {
+            global: false,
+            config_dir: Some(dir.path().to_path_buf()),
+        };
+        let contract_id = stellar_strkey::Contract::from_string(CONTRACT_ID)
+            .unwrap()
+            .0;
+        locator
+            .save_contract_id("Test Network", CONTRACT_ID, "dead-beef")
+            .unwrap();
+        // Saved before hex-looking aliases were rejected.
+        fs::create_dir_all(dir.path().join(".soroban/contract-ids")).unwrap();
+        fs::write(
+            dir.path().join(".soroban/contract-ids/cafe.toml"),
+            format!("[ids]\n\"Test Network\" = \"{CONTRACT_ID}\"\n"),
+        )
+        .unwrap();
+        for alias in ["dead-beef", "cafe"] {
+            assert_eq!(
+                locator.resolve_contract_id(alias, "Test Network").unwrap(),
+                contract_id
+            );
+        }
+        assert_eq!(
+            locator
+                .resolve_contract_id("cafe", "Other Network")
+                .unwrap(),
+            utils::contract_id_from_str("cafe").unwrap()
+        );
+    }
+}
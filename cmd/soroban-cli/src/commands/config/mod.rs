@@ -2,14 +2,18 @@ use std::path::PathBuf;
 
 use clap::{arg, command};
 use serde::{Deserialize, Serialize};
+use soroban_env_host::xdr::{MuxedAccount, MuxedAccountMed25519, Uint256};
 
-use crate::Pwd;
+use crate::{signer::Signer, Pwd};
 
 use self::{network::Network, secret::Secret};
 
 use super::{keys, network};
 
+pub mod backend;
+pub mod channel;
 pub mod data;
+pub mod encryption;
 pub mod locator;
 pub mod secret;
 
@@ -32,7 +36,7 @@ pub struct Args {
     pub network: network::Args,
 
     #[arg(long, visible_alias = "source", env = "STELLAR_ACCOUNT")]
-    /// Account that signs the final transaction. Alias `source`. Can be an identity (--source alice), a secret key (--source SC36…), or a seed phrase (--source "kite urban…"). Default: `identity generate --default-seed`
+    /// Account that signs the final transaction. Alias `source`. Can be an identity (--source alice), a secret key (--source SC36…), a seed phrase (--source "kite urban…"), an environment variable holding either (--source `env:CI_SECRET_KEY`), a muxed address (--source MA7Q…) of an identity's account, or a public key (--source GA7Q…) to build a transaction with --build-only. Default: `identity generate --default-seed`
     pub source_account: String,
 
     #[arg(long)]
@@ -49,14 +53,72 @@ impl Args {
         Ok(key.key_pair(self.hd_path)?)
     }
 
+    /// Signer of the source account, which may be an external signer.
+    pub fn signer(&self) -> Result<Signer, Error> {
+        Ok(self.account(&self.source_account)?.signer(self.hd_path)?)
+    }
+
+    /// Public key of the source account, which needs no secret, e.g. to build a transaction of
+    /// an address-only identity with `--build-only`.
+    pub fn public_key(&self) -> Result<stellar_strkey::ed25519::PublicKey, Error> {
+        Ok(self
+            .account(&self.source_account)?
+            .public_key(self.hd_path)?)
+    }
+
     pub fn account(&self, account_str: &str) -> Result<Secret, Error> {
         if let Ok(secret) = self.locator.read_identity(account_str) {
             Ok(secret)
+        } else if let Ok(muxed) = stellar_strkey::ed25519::MuxedAccount::from_string(account_str) {
+            Ok(self.locator.identity_for_public_key(
+                &stellar_strkey::ed25519::PublicKey(muxed.ed25519),
+                self.hd_path,
+            )?)
         } else {
             Ok(account_str.parse::<Secret>()?)
         }
     }
 
+    /// Source account of the transactions, muxed if `--source-account` is a muxed address or an
+    /// identity with a mux id.
+    pub fn source_muxed_account(&self) -> Result<MuxedAccount, Error> {
+        let ed25519 = Uint256(self.public_key()?.0);
+        let mux_id = if let Ok(muxed) =
+            stellar_strkey::ed25519::MuxedAccount::from_string(&self.source_account)
+        {
+            Some(muxed.id)
+        } else {
+            self.locator
+                .read_identity_mux_id(&self.source_account)
+                .ok()
+                .flatten()
+        };
+        Ok(match mux_id {
+            Some(id) => MuxedAccount::MuxedEd25519(MuxedAccountMed25519 { id, ed25519 }),
+            None => MuxedAccount::Ed25519(ed25519),
+        })
+    }
+
+    /// Account of `address`, which can be a public key, a muxed address or an identity, muxed if
+    /// the identity has a mux id.
+    pub fn muxed_account(&self, address: &str) -> Result<MuxedAccount, Error> {
+        if let Ok(key) = stellar_strkey::ed25519::PublicKey::from_string(address) {
+            return Ok(MuxedAccount::Ed25519(Uint256(key.0)));
+        }
+        if let Ok(muxed) = stellar_strkey::ed25519::MuxedAccount::from_string(address) {
+            return Ok(MuxedAccount::MuxedEd25519(MuxedAccountMed25519 {
+                id: muxed.id,
+                ed25519: Uint256(muxed.ed25519),
+            }));
+        }
+        let ed25519 = Uint256(self.locator.read_identity(address)?.public_key(None)?.0);
+        let mux_id = self.locator.read_identity_mux_id(address).ok().flatten();
+        Ok(match mux_id {
+            Some(id) => MuxedAccount::MuxedEd25519(MuxedAccountMed25519 { id, ed25519 }),
+            None => MuxedAccount::Ed25519(ed25519),
+        })
+    }
+
     pub fn get_network(&self) -> Result<Network, Error> {
         Ok(self.network.get(&self.locator)?)
     }
@@ -1,16 +1,37 @@
 use clap::arg;
 use serde::{Deserialize, Serialize};
-use std::{io::Write, str::FromStr};
+use std::{io::Write, str::FromStr, sync::OnceLock};
 use stellar_strkey::ed25519::{PrivateKey, PublicKey};
 
-use crate::utils;
+use crate::{signer, utils};
+
+use super::{
+    backend::{Backend, EncryptedFile, EnvVar, ExternalCommand, Kind, PlaintextFile, WatchOnly},
+    encryption,
+};
+
+/// Prefix of a reference to an environment variable holding a secret key or seed phrase.
+pub const ENV_PREFIX: &str = "env:";
+
+/// Environment variable holding the BIP-39 passphrase of seed phrases that have one, read instead
+/// of prompting for it.
+pub const MNEMONIC_PASSPHRASE_ENV: &str = "STELLAR_MNEMONIC_PASSPHRASE";
+
+/// Number of words of the seed phrases accepted, from 128 to 256 bits of entropy.
+const SEED_PHRASE_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("invalid secret key")]
     InvalidSecretKey,
-    // #[error("seed_phrase must be 12 words long, found {len}")]
-    // InvalidSeedPhrase { len: usize },
+    #[error("seed phrase must be 12, 15, 18, 21 or 24 words long, found {len}")]
+    InvalidSeedPhraseLength { len: usize },
+    #[error("invalid seed phrase ({0}), check the spelling and order of its words")]
+    InvalidSeedPhrase(sep5::error::Error),
+    #[error("the mnemonic passphrase cannot be empty, add the seed phrase without --mnemonic-passphrase instead")]
+    EmptyMnemonicPassphrase,
+    #[error("wrong mnemonic passphrase, the seed phrase derives another account than {0}")]
+    WrongMnemonicPassphrase(String),
     #[error("secret input error")]
     PasswordRead,
     #[error(transparent)]
@@ -21,6 +42,20 @@ pub enum Error {
     Ed25519(#[from] ed25519_dalek::SignatureError),
     #[error("Invalid address {0}")]
     InvalidAddress(String),
+    #[error("the identity signs with the external signer {0:?} and has no secret key")]
+    ExternalSigner(String),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+    #[error(transparent)]
+    Encryption(#[from] encryption::Error),
+    #[error("the secret of the identity is kept by the {0} backend and cannot be encrypted")]
+    CannotEncrypt(Kind),
+    #[error("environment variable {0} holding the identity's secret is not set")]
+    MissingEnvVar(String),
+    #[error("environment variable {0} does not hold a secret key or seed phrase")]
+    InvalidEnvVar(String),
+    #[error("{0} is an address-only identity, it has no secret key to sign with")]
+    WatchOnly(String),
 }
 
 #[derive(Debug, clap::Args, Clone)]
@@ -30,14 +65,43 @@ pub struct Args {
     /// Can provide with SOROBAN_SECRET_KEY
     #[arg(long, conflicts_with = "seed_phrase")]
     pub secret_key: bool,
-    /// Add using 12 word seed phrase to generate secret_key
+    /// Add using 12, 15, 18, 21 or 24 word seed phrase to generate secret_key
     #[arg(long, conflicts_with = "secret_key")]
     pub seed_phrase: bool,
+    /// Derive the keys of the seed phrase with a BIP-39 passphrase (the "25th word"), read from `STELLAR_MNEMONIC_PASSPHRASE` or prompted for. It is asked for whenever the identity signs, unless the identity is encrypted: it is then encrypted together with the seed phrase
+    #[arg(long, requires = "seed_phrase")]
+    pub mnemonic_passphrase: bool,
+    /// Add an identity signing with an external executable instead of a secret key, given as `cmd:` followed by its path and arguments, e.g. `cmd:/usr/local/bin/kms-signer --key-id 1`
+    #[arg(long, conflicts_with_all = ["secret_key", "seed_phrase"])]
+    pub signer: Option<String>,
+    /// Add an identity whose secret key or seed phrase is read from this environment variable whenever it is used, instead of being written to disk, e.g. to inject it in CI
+    #[arg(long, value_name = "VAR", conflicts_with_all = ["secret_key", "seed_phrase", "signer"])]
+    pub secret_env: Option<String>,
+    /// Add an address-only identity with the public key of an account, e.g. a partner's or a multisig co-signer's. It can be used in place of the address, but cannot sign
+    #[arg(long, value_name = "G_ADDRESS", conflicts_with_all = ["secret_key", "seed_phrase", "signer", "secret_env"])]
+    pub public_key: Option<String>,
 }
 
 impl Args {
     pub fn read_secret(&self) -> Result<Secret, Error> {
-        if let Ok(secret_key) = std::env::var("SOROBAN_SECRET_KEY") {
+        if let Some(signer) = &self.signer {
+            // Checks that the signer runs and has a key before the identity is saved
+            let public_key = signer::Signer::External(signer::External::new(signer)?).public_key();
+            Ok(Secret::ExternalSigner {
+                signer: signer.clone(),
+                public_key: Some(public_key.to_string()),
+            })
+        } else if let Some(public_key) = &self.public_key {
+            Ok(Secret::PublicKey {
+                public_key: PublicKey::from_string(public_key)
+                    .map_err(|_| Error::InvalidAddress(public_key.clone()))?
+                    .to_string(),
+            })
+        } else if let Some(secret_env) = &self.secret_env {
+            Ok(Secret::EnvVar {
+                secret_env: secret_env.clone(),
+            })
+        } else if let Ok(secret_key) = std::env::var("SOROBAN_SECRET_KEY") {
             Ok(Secret::SecretKey { secret_key })
         } else if self.secret_key {
             println!("Type a secret key: ");
@@ -47,20 +111,21 @@ impl Args {
                 .to_string();
             Ok(Secret::SecretKey { secret_key })
         } else if self.seed_phrase {
-            println!("Type a 12 word seed phrase: ");
-            let seed_phrase = read_password()?;
-            let seed_phrase: Vec<&str> = seed_phrase.split_whitespace().collect();
-            // if seed_phrase.len() != 12 {
-            //     let len = seed_phrase.len();
-            //     return Err(Error::InvalidSeedPhrase { len });
-            // }
-            Ok(Secret::SeedPhrase {
-                seed_phrase: seed_phrase
-                    .into_iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" "),
-            })
+            println!("Type a 12, 15, 18, 21 or 24 word seed phrase: ");
+            let seed_phrase = validate_seed_phrase(&read_password()?)?;
+            if self.mnemonic_passphrase {
+                let passphrase = mnemonic_passphrase()?;
+                let public_key = sep5::SeedPhrase::from_str(&seed_phrase)?
+                    .from_path_index(0, Some(&passphrase))?
+                    .public()
+                    .to_string();
+                Ok(Secret::SeedPhraseWithPassphrase {
+                    public_key,
+                    seed_phrase,
+                })
+            } else {
+                Ok(Secret::SeedPhrase { seed_phrase })
+            }
         } else {
             Err(Error::PasswordRead {})
         }
@@ -70,8 +135,33 @@ impl Args {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Secret {
-    SecretKey { secret_key: String },
-    SeedPhrase { seed_phrase: String },
+    SecretKey {
+        secret_key: String,
+    },
+    SeedPhraseWithPassphrase {
+        public_key: String,
+        seed_phrase: String,
+    },
+    SeedPhrase {
+        seed_phrase: String,
+    },
+    ExternalSigner {
+        signer: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public_key: Option<String>,
+    },
+    Encrypted {
+        public_key: String,
+        salt: String,
+        nonce: String,
+        encrypted_secret: String,
+    },
+    EnvVar {
+        secret_env: String,
+    },
+    PublicKey {
+        public_key: String,
+    },
 }
 
 impl FromStr for Secret {
@@ -82,6 +172,19 @@ impl FromStr for Secret {
             Ok(Secret::SecretKey {
                 secret_key: s.to_string(),
             })
+        } else if PublicKey::from_string(s).is_ok() {
+            Ok(Secret::PublicKey {
+                public_key: s.to_string(),
+            })
+        } else if let Some(secret_env) = s.strip_prefix(ENV_PREFIX) {
+            Ok(Secret::EnvVar {
+                secret_env: secret_env.to_string(),
+            })
+        } else if s.starts_with(signer::COMMAND_PREFIX) {
+            Ok(Secret::ExternalSigner {
+                signer: s.to_string(),
+                public_key: None,
+            })
         } else if sep5::SeedPhrase::from_str(s).is_ok() {
             Ok(Secret::SeedPhrase {
                 seed_phrase: s.to_string(),
@@ -101,34 +204,100 @@ impl From<PrivateKey> for Secret {
 }
 
 impl Secret {
+    /// Backend keeping the secret of the identity.
+    pub fn backend(&self) -> Box<dyn Backend + '_> {
+        match self {
+            Secret::SecretKey { secret_key } => Box::new(PlaintextFile::SecretKey(secret_key)),
+            Secret::SeedPhrase { seed_phrase } => Box::new(PlaintextFile::SeedPhrase(seed_phrase)),
+            Secret::SeedPhraseWithPassphrase {
+                public_key,
+                seed_phrase,
+            } => Box::new(PlaintextFile::SeedPhraseWithPassphrase {
+                seed_phrase,
+                public_key,
+            }),
+            Secret::ExternalSigner { signer, public_key } => Box::new(ExternalCommand {
+                signer,
+                public_key: public_key.as_deref(),
+            }),
+            Secret::Encrypted {
+                public_key,
+                salt,
+                nonce,
+                encrypted_secret,
+            } => Box::new(EncryptedFile {
+                public_key,
+                encrypted: encryption::Encrypted {
+                    salt: salt.clone(),
+                    nonce: nonce.clone(),
+                    ciphertext: encrypted_secret.clone(),
+                },
+            }),
+            Secret::EnvVar { secret_env } => Box::new(EnvVar { name: secret_env }),
+            Secret::PublicKey { public_key } => Box::new(WatchOnly { public_key }),
+        }
+    }
+
     pub fn private_key(&self, index: Option<usize>) -> Result<PrivateKey, Error> {
-        Ok(match self {
-            Secret::SecretKey { secret_key } => PrivateKey::from_string(secret_key)?,
-            Secret::SeedPhrase { seed_phrase } => PrivateKey::from_payload(
-                &sep5::SeedPhrase::from_str(seed_phrase)?
-                    .from_path_index(index.unwrap_or_default(), None)?
-                    .private()
-                    .0,
-            )?,
-        })
+        self.backend().private_key(index)
     }
 
     pub fn public_key(&self, index: Option<usize>) -> Result<PublicKey, Error> {
-        let key = self.key_pair(index)?;
-        Ok(stellar_strkey::ed25519::PublicKey::from_payload(
-            key.verifying_key().as_bytes(),
-        )?)
+        self.backend().public_key(index)
+    }
+
+    /// Public key, when it is known without running an external signer or prompting for a
+    /// passphrase.
+    pub fn known_public_key(&self, index: Option<usize>) -> Option<PublicKey> {
+        self.backend().known_public_key(index)
     }
 
     pub fn key_pair(&self, index: Option<usize>) -> Result<ed25519_dalek::SigningKey, Error> {
         Ok(utils::into_signing_key(&self.private_key(index)?))
     }
 
-    pub fn from_seed(seed: Option<&str>) -> Result<Self, Error> {
+    /// Signer of the transactions of the identity, which runs its external signer if it has one.
+    pub fn signer(&self, index: Option<usize>) -> Result<signer::Signer, Error> {
+        self.backend().signer(index)
+    }
+
+    /// Whether the secret is written as is in the identity file, and can be encrypted.
+    pub fn is_encryptable(&self) -> bool {
+        self.backend().kind() == Kind::PlaintextFile
+    }
+
+    /// The secret encrypted with `passphrase`, to be stored instead of the plaintext one. A seed
+    /// phrase with a mnemonic passphrase is encrypted together with it, once the passphrase is
+    /// checked, so that it is not asked for again.
+    pub fn encrypt(&self, passphrase: &str) -> Result<Self, Error> {
+        let plaintext = match self {
+            Secret::SecretKey { secret_key } => secret_key.clone(),
+            Secret::SeedPhrase { seed_phrase } => seed_phrase.clone(),
+            Secret::SeedPhraseWithPassphrase { seed_phrase, .. } => {
+                self.private_key(None)?;
+                format!("{seed_phrase}\n{}", mnemonic_passphrase()?)
+            }
+            _ => return Err(Error::CannotEncrypt(self.backend().kind())),
+        };
+        let encryption::Encrypted {
+            salt,
+            nonce,
+            ciphertext,
+        } = encryption::encrypt(&plaintext, passphrase)?;
+        Ok(Secret::Encrypted {
+            public_key: self.public_key(None)?.to_string(),
+            salt,
+            nonce,
+            encrypted_secret: ciphertext,
+        })
+    }
+
+    /// Seed phrase from the entropy `seed`, or else a random one of `words` words.
+    pub fn from_seed(seed: Option<&str>, words: sep5::MnemonicType) -> Result<Self, Error> {
         let seed_phrase = if let Some(seed) = seed.map(str::as_bytes) {
             sep5::SeedPhrase::from_entropy(seed)
         } else {
-            sep5::SeedPhrase::random(sep5::MnemonicType::Words12)
+            sep5::SeedPhrase::random(words)
         }?
         .seed_phrase
         .into_phrase();
@@ -136,11 +305,70 @@ impl Secret {
     }
 
     pub fn test_seed_phrase() -> Result<Self, Error> {
-        Self::from_seed(Some("0000000000000000"))
+        Self::from_seed(Some("0000000000000000"), sep5::MnemonicType::Words12)
     }
 }
 
+/// The words of `seed_phrase` separated by single spaces, once its length and checksum are checked.
+fn validate_seed_phrase(seed_phrase: &str) -> Result<String, Error> {
+    let words = seed_phrase.split_whitespace().collect::<Vec<_>>();
+    if !SEED_PHRASE_LENGTHS.contains(&words.len()) {
+        return Err(Error::InvalidSeedPhraseLength { len: words.len() });
+    }
+    let seed_phrase = words.join(" ");
+    sep5::SeedPhrase::from_str(&seed_phrase).map_err(Error::InvalidSeedPhrase)?;
+    Ok(seed_phrase)
+}
+
+/// Mnemonic passphrase entered once per run.
+static MNEMONIC_PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// BIP-39 passphrase of seed phrases that have one, from `STELLAR_MNEMONIC_PASSPHRASE` or else
+/// prompted for.
+pub fn mnemonic_passphrase() -> Result<String, Error> {
+    let passphrase = if let Ok(passphrase) = std::env::var(MNEMONIC_PASSPHRASE_ENV) {
+        passphrase
+    } else if let Some(passphrase) = MNEMONIC_PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    } else {
+        let passphrase =
+            rpassword::prompt_password("Mnemonic passphrase: ").map_err(|_| Error::PasswordRead)?;
+        MNEMONIC_PASSPHRASE.get_or_init(|| passphrase).clone()
+    };
+    if passphrase.is_empty() {
+        return Err(Error::EmptyMnemonicPassphrase);
+    }
+    Ok(passphrase)
+}
+
 fn read_password() -> Result<String, Error> {
     std::io::stdout().flush().map_err(|_| Error::PasswordRead)?;
     rpassword::read_password().map_err(|_| Error::PasswordRead)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_seed_phrase() {
+        let Secret::SeedPhrase { seed_phrase } =
+            Secret::from_seed(None, sep5::MnemonicType::Words24).unwrap()
+        else {
+            panic!("expected a seed phrase");
+        };
+        assert_eq!(seed_phrase.split_whitespace().count(), 24);
+        assert_eq!(
+            validate_seed_phrase(&format!("  {}\n", seed_phrase.replace(' ', "   "))).unwrap(),
+            seed_phrase
+        );
+        assert!(matches!(
+            validate_seed_phrase("one two three four five six seven eight nine ten eleven"),
+            Err(Error::InvalidSeedPhraseLength { len: 11 })
+        ));
+        assert!(matches!(
+            validate_seed_phrase(&["abandon"; 12].join(" ")),
+            Err(Error::InvalidSeedPhrase(_))
+        ));
+    }
+}
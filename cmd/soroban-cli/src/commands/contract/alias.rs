@@ -0,0 +1,40 @@
+pub mod add;
+pub mod ls;
+pub mod rm;
+pub mod show;
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Cmd {
+    /// Name a contract ID on a network, so that the name can be used in its place
+    Add(add::Cmd),
+    /// Remove the contract of an alias on a network
+    Rm(rm::Cmd),
+    /// List contract aliases and their contract IDs on every network
+    Ls(ls::Cmd),
+    /// Print the contract ID of an alias on a network
+    Show(show::Cmd),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Add(#[from] add::Error),
+    #[error(transparent)]
+    Rm(#[from] rm::Error),
+    #[error(transparent)]
+    Ls(#[from] ls::Error),
+    #[error(transparent)]
+    Show(#[from] show::Error),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        match &self {
+            Cmd::Add(add) => add.run()?,
+            Cmd::Rm(rm) => rm.run()?,
+            Cmd::Ls(ls) => ls.run()?,
+            Cmd::Show(show) => show.run()?,
+        }
+        Ok(())
+    }
+}
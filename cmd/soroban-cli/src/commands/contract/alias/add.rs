@@ -0,0 +1,39 @@
+use clap::{arg, command, Parser};
+
+use crate::commands::{config::locator, network};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Locator(#[from] locator::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Alias of the contract
+    pub alias: String,
+
+    /// Contract ID the alias names on the network
+    #[arg(long = "id")]
+    pub contract_id: String,
+
+    #[command(flatten)]
+    pub network: network::Args,
+
+    #[command(flatten)]
+    pub config_locator: locator::Args,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let network = self.network.get(&self.config_locator)?;
+        Ok(self.config_locator.save_contract_id(
+            &network.network_passphrase,
+            &self.contract_id,
+            &self.alias,
+        )?)
+    }
+}
@@ -0,0 +1,39 @@
+use clap::{command, Parser};
+
+use crate::commands::config::locator;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Locator(#[from] locator::Error),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub config_locator: locator::Args,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        println!("{}", self.ls()?.join("\n"));
+        Ok(())
+    }
+
+    pub fn ls(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .config_locator
+            .list_contract_aliases()?
+            .into_iter()
+            .flat_map(|(alias, contract_ids)| {
+                contract_ids
+                    .ids
+                    .into_iter()
+                    .map(move |(network_passphrase, contract_id)| {
+                        format!("{alias}: {contract_id} ({network_passphrase})")
+                    })
+            })
+            .collect())
+    }
+}
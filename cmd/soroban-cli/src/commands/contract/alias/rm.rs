@@ -0,0 +1,33 @@
+use clap::{command, Parser};
+
+use crate::commands::{config::locator, network};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Locator(#[from] locator::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Alias to remove
+    pub alias: String,
+
+    #[command(flatten)]
+    pub network: network::Args,
+
+    #[command(flatten)]
+    pub config_locator: locator::Args,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let network = self.network.get(&self.config_locator)?;
+        Ok(self
+            .config_locator
+            .remove_contract_id(&network.network_passphrase, &self.alias)?)
+    }
+}
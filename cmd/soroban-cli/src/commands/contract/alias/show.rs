@@ -0,0 +1,38 @@
+use clap::{command, Parser};
+
+use crate::commands::{config::locator, network};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Locator(#[from] locator::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error("No contract found for alias {0:?} on this network")]
+    NotFound(String),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Alias of the contract
+    pub alias: String,
+
+    #[command(flatten)]
+    pub network: network::Args,
+
+    #[command(flatten)]
+    pub config_locator: locator::Args,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let network = self.network.get(&self.config_locator)?;
+        let contract_id = self
+            .config_locator
+            .get_contract_id(&self.alias, &network.network_passphrase)?
+            .ok_or_else(|| Error::NotFound(self.alias.clone()))?;
+        println!("{contract_id}");
+        Ok(())
+    }
+}
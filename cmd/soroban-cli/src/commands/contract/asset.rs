@@ -1,6 +1,7 @@
 use super::{deploy, id};
 
 #[derive(Debug, clap::Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Cmd {
     /// Get Id of builtin Soroban Asset Contract. Deprecated, use `soroban contract id asset` instead
     Id(id::asset::Cmd),
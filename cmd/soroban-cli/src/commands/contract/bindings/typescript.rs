@@ -23,7 +23,7 @@ pub struct Cmd {
     /// Whether to overwrite output directory if it already exists
     #[arg(long)]
     overwrite: bool,
-    /// The contract ID/address on the network
+    /// The contract ID/address on the network, or its alias
     #[arg(long, visible_alias = "id")]
     contract_id: String,
     #[command(flatten)]
@@ -65,12 +65,26 @@ pub enum Error {
 
 impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
+        let Network {
+            rpc_url,
+            network_passphrase,
+            ..
+        } = self
+            .network
+            .get(&self.locator)
+            .ok()
+            .unwrap_or_else(Network::futurenet);
+        let contract_id = stellar_strkey::Contract(
+            self.locator
+                .resolve_contract_id(&self.contract_id, &network_passphrase)?,
+        )
+        .to_string();
         let spec = if let Some(wasm) = &self.wasm {
             let wasm: wasm::Args = wasm.into();
             wasm.parse()?.spec
         } else {
             let fetch = contract::fetch::Cmd {
-                contract_id: self.contract_id.clone(),
+                contract_id: contract_id.clone(),
                 out_file: None,
                 locator: self.locator.clone(),
                 network: self.network.clone(),
@@ -90,15 +104,6 @@ impl Cmd {
         }
         std::fs::create_dir_all(&self.output_dir)?;
         let p: Project = self.output_dir.clone().try_into()?;
-        let Network {
-            rpc_url,
-            network_passphrase,
-            ..
-        } = self
-            .network
-            .get(&self.locator)
-            .ok()
-            .unwrap_or_else(Network::futurenet);
         let absolute_path = self.output_dir.canonicalize()?;
         let file_name = absolute_path
             .file_name()
@@ -108,7 +113,7 @@ impl Cmd {
             .ok_or_else(|| Error::NotUtf8(file_name.to_os_string()))?;
         p.init(
             contract_name,
-            &self.contract_id,
+            &contract_id,
             &rpc_url,
             &network_passphrase,
             &spec,
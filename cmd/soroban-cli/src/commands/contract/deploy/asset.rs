@@ -3,8 +3,8 @@ use soroban_env_host::{
     xdr::{
         Asset, ContractDataDurability, ContractExecutable, ContractIdPreimage, CreateContractArgs,
         Error as XdrError, Hash, HostFunction, InvokeHostFunctionOp, LedgerKey::ContractData,
-        LedgerKeyContractData, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
-        ScAddress, ScVal, SequenceNumber, Transaction, TransactionExt, Uint256, VecM, WriteXdr,
+        LedgerKeyContractData, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+        ScAddress, ScVal, SequenceNumber, Transaction, TransactionExt, VecM,
     },
     HostError,
 };
@@ -32,6 +32,8 @@ pub enum Error {
     ParseIntError(#[from] ParseIntError),
     #[error(transparent)]
     Client(#[from] SorobanRpcError),
+    #[error(transparent)]
+    Fee(#[from] crate::fee::Error),
     #[error("internal conversion error: {0}")]
     TryFromSliceError(#[from] TryFromSliceError),
     #[error("xdr processing error: {0}")]
@@ -69,7 +71,7 @@ impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
         let res = self.run_against_rpc_server(None, None).await?.to_envelope();
         match res {
-            TxnEnvelopeResult::TxnEnvelope(tx) => println!("{}", tx.to_xdr_base64(Limits::none())?),
+            TxnEnvelopeResult::TxnEnvelope(tx) => self.fee.print_envelope(&tx, &self.config)?,
             TxnEnvelopeResult::Res(contract) => {
                 println!("{contract}");
             }
@@ -94,37 +96,35 @@ impl NetworkRunnable for Cmd {
 
         let network = config.get_network()?;
         let client = Client::new(&network.rpc_url)?;
-        client
-            .verify_network_passphrase(Some(&network.network_passphrase))
-            .await?;
-        let key = config.key_pair()?;
-
-        // Get the account sequence number
-        let public_strkey =
-            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
-        // TODO: use symbols for the method names (both here and in serve)
-        let account_details = client.get_account(&public_strkey).await?;
-        let sequence: i64 = account_details.seq_num.into();
+        if !self.fee.build_only {
+            client
+                .verify_network_passphrase(Some(&network.network_passphrase))
+                .await?;
+        }
+        let sequence = self.fee.next_sequence(&client, config).await?;
         let network_passphrase = &network.network_passphrase;
         let contract_id = contract_id_hash_from_asset(&asset, network_passphrase)?;
         let tx = build_wrap_token_tx(
             &asset,
             &contract_id,
-            sequence + 1,
+            sequence,
             self.fee.fee,
             network_passphrase,
-            &key,
+            &config.source_muxed_account()?,
         )?;
+        let tx = self.fee.apply_to_txn(tx)?;
         if self.fee.build_only {
             return Ok(TxnResult::Txn(tx));
         }
         let txn = client.create_assembled_transaction(&tx).await?;
-        let txn = self.fee.apply_to_assembled_txn(txn);
         if self.fee.sim_only {
-            return Ok(TxnResult::Txn(txn.transaction().clone()));
+            return Ok(TxnResult::Txn(
+                self.fee.apply_to_simulated_txn(txn.transaction().clone())?,
+            ));
         }
-        let get_txn_resp = client
-            .send_assembled_transaction(txn, &key, &[], network_passphrase, None, None)
+        let get_txn_resp = self
+            .fee
+            .send_assembled_transaction(&client, txn, &[], config, None, None)
             .await?
             .try_into()?;
         if args.map_or(true, |a| !a.no_cache) {
@@ -141,7 +141,7 @@ fn build_wrap_token_tx(
     sequence: i64,
     fee: u32,
     _network_passphrase: &str,
-    key: &ed25519_dalek::SigningKey,
+    source_account: &MuxedAccount,
 ) -> Result<Transaction, Error> {
     let contract = ScAddress::Contract(contract_id.clone());
     let mut read_write = vec![
@@ -180,7 +180,7 @@ fn build_wrap_token_tx(
     };
 
     Ok(Transaction {
-        source_account: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+        source_account: source_account.clone(),
         fee,
         seq_num: SequenceNumber(sequence),
         cond: Preconditions::None,
@@ -7,9 +7,9 @@ use rand::Rng;
 use soroban_env_host::{
     xdr::{
         AccountId, ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress,
-        CreateContractArgs, Error as XdrError, Hash, HostFunction, InvokeHostFunctionOp, Limits,
-        Memo, MuxedAccount, Operation, OperationBody, Preconditions, PublicKey, ScAddress,
-        SequenceNumber, Transaction, TransactionExt, Uint256, VecM, WriteXdr,
+        CreateContractArgs, Error as XdrError, Hash, HostFunction, InvokeHostFunctionOp, Memo,
+        MuxedAccount, Operation, OperationBody, Preconditions, PublicKey, ScAddress,
+        SequenceNumber, Transaction, TransactionExt, Uint256, VecM,
     },
     HostError,
 };
@@ -54,6 +54,9 @@ pub struct Cmd {
     #[arg(long, short = 'i', default_value = "false")]
     /// Whether to ignore safety checks when deploying contracts
     pub ignore_checks: bool,
+    /// Alias to record for the deployed contract on the network, usable in place of its ID
+    #[arg(long)]
+    pub alias: Option<String>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -82,13 +85,19 @@ pub enum Error {
         wasm_hash: String,
         error: stellar_strkey::DecodeError,
     },
+    #[error("--sequence can only be used with --wasm-hash, or with --build-only, as installing the contract and deploying it are separate transactions")]
+    SequenceWithInstall,
     #[error("Must provide either --wasm or --wash-hash")]
     WasmNotProvided,
     #[error(transparent)]
     Rpc(#[from] rpc::Error),
     #[error(transparent)]
+    Fee(#[from] crate::fee::Error),
+    #[error(transparent)]
     Config(#[from] config::Error),
     #[error(transparent)]
+    Locator(#[from] config::locator::Error),
+    #[error(transparent)]
     StrKey(#[from] stellar_strkey::DecodeError),
     #[error(transparent)]
     Infallible(#[from] std::convert::Infallible),
@@ -106,7 +115,7 @@ impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
         let res = self.run_against_rpc_server(None, None).await?.to_envelope();
         match res {
-            TxnEnvelopeResult::TxnEnvelope(tx) => println!("{}", tx.to_xdr_base64(Limits::none())?),
+            TxnEnvelopeResult::TxnEnvelope(tx) => self.fee.print_envelope(&tx, &self.config)?,
             TxnEnvelopeResult::Res(contract) => {
                 println!("{contract}");
             }
@@ -126,9 +135,14 @@ impl NetworkRunnable for Cmd {
         config: Option<&config::Args>,
     ) -> Result<TxnResult<String>, Error> {
         let config = config.unwrap_or(&self.config);
+        if let Some(alias) = &self.alias {
+            config::locator::validate_contract_alias(alias)?;
+        }
         let wasm_hash = if let Some(wasm) = &self.wasm {
             let hash = if self.fee.build_only {
                 wasm::Args { wasm: wasm.clone() }.hash()?
+            } else if self.fee.sequence.sequence.is_some() {
+                return Err(Error::SequenceWithInstall);
             } else {
                 install::Cmd {
                     wasm: wasm::Args { wasm: wasm.clone() },
@@ -165,44 +179,46 @@ impl NetworkRunnable for Cmd {
         };
 
         let client = Client::new(&network.rpc_url)?;
-        client
-            .verify_network_passphrase(Some(&network.network_passphrase))
-            .await?;
-        let key = config.key_pair()?;
-
-        // Get the account sequence number
-        let public_strkey =
-            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
-
-        let account_details = client.get_account(&public_strkey).await?;
-        let sequence: i64 = account_details.seq_num.into();
+        if !self.fee.build_only {
+            client
+                .verify_network_passphrase(Some(&network.network_passphrase))
+                .await?;
+        }
+        let sequence = self.fee.next_sequence(&client, config).await?;
         let (txn, contract_id) = build_create_contract_tx(
             wasm_hash,
-            sequence + 1,
+            sequence,
             self.fee.fee,
             &network.network_passphrase,
             salt,
-            &key,
+            &config.source_muxed_account()?,
         )?;
+        let txn = self.fee.apply_to_txn(txn)?;
         if self.fee.build_only {
             return Ok(TxnResult::Txn(txn));
         }
 
         let txn = client.create_assembled_transaction(&txn).await?;
-        let txn = self.fee.apply_to_assembled_txn(txn);
         if self.fee.sim_only {
-            return Ok(TxnResult::Txn(txn.transaction().clone()));
+            return Ok(TxnResult::Txn(
+                self.fee.apply_to_simulated_txn(txn.transaction().clone())?,
+            ));
         }
-        let get_txn_resp = client
-            .send_assembled_transaction(txn, &key, &[], &network.network_passphrase, None, None)
+        let get_txn_resp = self
+            .fee
+            .send_assembled_transaction(&client, txn, &[], config, None, None)
             .await?
             .try_into()?;
         if global_args.map_or(true, |a| !a.no_cache) {
             data::write(get_txn_resp, &network.rpc_uri()?)?;
         }
-        Ok(TxnResult::Res(
-            stellar_strkey::Contract(contract_id.0).to_string(),
-        ))
+        let contract_id = stellar_strkey::Contract(contract_id.0).to_string();
+        if let Some(alias) = &self.alias {
+            config
+                .locator
+                .save_contract_id(&network.network_passphrase, &contract_id, alias)?;
+        }
+        Ok(TxnResult::Res(contract_id))
     }
 }
 
@@ -212,14 +228,14 @@ fn build_create_contract_tx(
     fee: u32,
     network_passphrase: &str,
     salt: [u8; 32],
-    key: &ed25519_dalek::SigningKey,
+    source_account: &MuxedAccount,
 ) -> Result<(Transaction, Hash), Error> {
-    let source_account = AccountId(PublicKey::PublicKeyTypeEd25519(
-        key.verifying_key().to_bytes().into(),
+    let deployer = AccountId(PublicKey::PublicKeyTypeEd25519(
+        utils::muxed_account_ed25519(source_account),
     ));
 
     let contract_id_preimage = ContractIdPreimage::Address(ContractIdPreimageFromAddress {
-        address: ScAddress::Account(source_account),
+        address: ScAddress::Account(deployer),
         salt: Uint256(salt),
     });
     let contract_id = get_contract_id(contract_id_preimage.clone(), network_passphrase)?;
@@ -235,7 +251,7 @@ fn build_create_contract_tx(
         }),
     };
     let tx = Transaction {
-        source_account: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+        source_account: source_account.clone(),
         fee,
         seq_num: SequenceNumber(sequence),
         cond: Preconditions::None,
@@ -263,8 +279,12 @@ mod tests {
             1,
             "Public Global Stellar Network ; September 2015",
             [0u8; 32],
-            &utils::parse_secret_key("SBFGFF27Y64ZUGFAIG5AMJGQODZZKV2YQKAVUUN4HNE24XZXD2OEUVUP")
-                .unwrap(),
+            &MuxedAccount::Ed25519(Uint256(
+                utils::parse_secret_key("SBFGFF27Y64ZUGFAIG5AMJGQODZZKV2YQKAVUUN4HNE24XZXD2OEUVUP")
+                    .unwrap()
+                    .verifying_key()
+                    .to_bytes(),
+            )),
         );
 
         assert!(result.is_ok());
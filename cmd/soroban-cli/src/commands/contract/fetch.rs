@@ -24,14 +24,14 @@ use crate::commands::network::{self, Network};
 use crate::commands::{global, NetworkRunnable};
 use crate::{
     rpc::{self, Client},
-    utils, Pwd,
+    Pwd,
 };
 
 #[derive(Parser, Debug, Default, Clone)]
 #[allow(clippy::struct_excessive_bools)]
 #[group(skip)]
 pub struct Cmd {
-    /// Contract ID to fetch
+    /// Contract ID to fetch, or its alias on the network
     #[arg(long = "id", env = "STELLAR_CONTRACT_ID")]
     pub contract_id: String,
     /// Where to write output otherwise stdout is used
@@ -123,9 +123,10 @@ impl Cmd {
         Ok(self.network.get(&self.locator)?)
     }
 
-    fn contract_id(&self) -> Result<[u8; 32], Error> {
-        utils::contract_id_from_str(&self.contract_id)
-            .map_err(|e| Error::CannotParseContractId(self.contract_id.clone(), e))
+    fn contract_id(&self, network_passphrase: &str) -> Result<[u8; 32], Error> {
+        Ok(self
+            .locator
+            .resolve_contract_id(&self.contract_id, network_passphrase)?)
     }
 }
 
@@ -140,7 +141,7 @@ impl NetworkRunnable for Cmd {
     ) -> Result<Vec<u8>, Error> {
         let network = config.map_or_else(|| self.network(), |c| Ok(c.get_network()?))?;
         tracing::trace!(?network);
-        let contract_id = self.contract_id()?;
+        let contract_id = self.contract_id(&network.network_passphrase)?;
         let client = Client::new(&network.rpc_url)?;
         client
             .verify_network_passphrase(Some(&network.network_passphrase))
@@ -34,8 +34,7 @@ impl Cmd {
             .map_err(|_| Error::CannotParseSalt(self.salt.clone()))?
             .try_into()
             .map_err(|_| Error::CannotParseSalt(self.salt.clone()))?;
-        let contract_id_preimage =
-            contract_preimage(&self.config.key_pair()?.verifying_key(), salt);
+        let contract_id_preimage = contract_preimage(&self.config.public_key()?, salt);
         let contract_id = get_contract_id(
             contract_id_preimage.clone(),
             &self.config.get_network()?.network_passphrase,
@@ -46,8 +45,11 @@ impl Cmd {
     }
 }
 
-pub fn contract_preimage(key: &ed25519_dalek::VerifyingKey, salt: [u8; 32]) -> ContractIdPreimage {
-    let source_account = AccountId(PublicKey::PublicKeyTypeEd25519(key.to_bytes().into()));
+pub fn contract_preimage(
+    key: &stellar_strkey::ed25519::PublicKey,
+    salt: [u8; 32],
+) -> ContractIdPreimage {
+    let source_account = AccountId(PublicKey::PublicKeyTypeEd25519(key.0.into()));
     ContractIdPreimage::Address(ContractIdPreimageFromAddress {
         address: ScAddress::Account(source_account),
         salt: Uint256(salt),
@@ -7,7 +7,7 @@ use soroban_env_host::xdr::{
     self, ContractCodeEntryExt, Error as XdrError, Hash, HostFunction, InvokeHostFunctionOp,
     LedgerEntryData, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions, ReadXdr,
     ScMetaEntry, ScMetaV0, SequenceNumber, Transaction, TransactionExt, TransactionResult,
-    TransactionResultResult, Uint256, VecM, WriteXdr,
+    TransactionResultResult, VecM,
 };
 
 use super::restore;
@@ -48,6 +48,8 @@ pub enum Error {
     #[error(transparent)]
     Rpc(#[from] rpc::Error),
     #[error(transparent)]
+    Fee(#[from] crate::fee::Error),
+    #[error(transparent)]
     Config(#[from] config::Error),
     #[error(transparent)]
     Wasm(#[from] wasm::Error),
@@ -75,7 +77,7 @@ impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
         let res = self.run_against_rpc_server(None, None).await?.to_envelope();
         match res {
-            TxnEnvelopeResult::TxnEnvelope(tx) => println!("{}", tx.to_xdr_base64(Limits::none())?),
+            TxnEnvelopeResult::TxnEnvelope(tx) => self.fee.print_envelope(&tx, &self.config)?,
             TxnEnvelopeResult::Res(hash) => println!("{}", hex::encode(hash)),
         };
         Ok(())
@@ -86,6 +88,7 @@ impl Cmd {
 impl NetworkRunnable for Cmd {
     type Error = Error;
     type Result = TxnResult<Hash>;
+    #[allow(clippy::too_many_lines)]
     async fn run_against_rpc_server(
         &self,
         args: Option<&global::Args>,
@@ -95,9 +98,11 @@ impl NetworkRunnable for Cmd {
         let contract = self.wasm.read()?;
         let network = config.get_network()?;
         let client = Client::new(&network.rpc_url)?;
-        client
-            .verify_network_passphrase(Some(&network.network_passphrase))
-            .await?;
+        if !self.fee.build_only {
+            client
+                .verify_network_passphrase(Some(&network.network_passphrase))
+                .await?;
+        }
         let wasm_spec = &self.wasm.parse().map_err(|e| Error::CannotParseWasm {
             wasm: self.wasm.wasm.clone(),
             error: e,
@@ -118,16 +123,14 @@ impl NetworkRunnable for Cmd {
                 tracing::warn!("the deployed smart contract {path} was built with Soroban Rust SDK v{rs_sdk_ver}, a release candidate version not intended for use with the Stellar Public Network", path = self.wasm.wasm.display());
             }
         }
-        let key = config.key_pair()?;
-
-        // Get the account sequence number
-        let public_strkey =
-            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
-        let account_details = client.get_account(&public_strkey).await?;
-        let sequence: i64 = account_details.seq_num.into();
-
-        let (tx_without_preflight, hash) =
-            build_install_contract_code_tx(&contract, sequence + 1, self.fee.fee, &key)?;
+        let sequence = self.fee.next_sequence(&client, config).await?;
+        let (tx_without_preflight, hash) = build_install_contract_code_tx(
+            &contract,
+            sequence,
+            self.fee.fee,
+            &config.source_muxed_account()?,
+        )?;
+        let tx_without_preflight = self.fee.apply_to_txn(tx_without_preflight)?;
 
         if self.fee.build_only {
             return Ok(TxnResult::Txn(tx_without_preflight));
@@ -166,12 +169,14 @@ impl NetworkRunnable for Cmd {
         let txn = client
             .create_assembled_transaction(&tx_without_preflight)
             .await?;
-        let txn = self.fee.apply_to_assembled_txn(txn);
         if self.fee.sim_only {
-            return Ok(TxnResult::Txn(txn.transaction().clone()));
+            return Ok(TxnResult::Txn(
+                self.fee.apply_to_simulated_txn(txn.transaction().clone())?,
+            ));
         }
-        let txn_resp = client
-            .send_assembled_transaction(txn, &key, &[], &network.network_passphrase, None, None)
+        let txn_resp = self
+            .fee
+            .send_assembled_transaction(&client, txn, &[], config, None, None)
             .await?;
         if args.map_or(true, |a| !a.no_cache) {
             data::write(txn_resp.clone().try_into().unwrap(), &network.rpc_uri()?)?;
@@ -193,7 +198,14 @@ impl NetworkRunnable for Cmd {
                     durability: super::Durability::Persistent,
                 },
                 config: config.clone(),
-                fee: self.fee.clone(),
+                // The install used the --sequence given, the restore takes the next one
+                fee: crate::fee::Args {
+                    sequence: crate::sequence::Args {
+                        sequence: self.fee.sequence.sequence.map(|s| s + 1),
+                        ..self.fee.sequence.clone()
+                    },
+                    ..self.fee.clone()
+                },
                 ledgers_to_extend: None,
                 ttl_ledger_only: true,
             }
@@ -231,14 +243,12 @@ pub(crate) fn build_install_contract_code_tx(
     source_code: &[u8],
     sequence: i64,
     fee: u32,
-    key: &ed25519_dalek::SigningKey,
+    source_account: &MuxedAccount,
 ) -> Result<(Transaction, Hash), XdrError> {
     let hash = utils::contract_hash(source_code)?;
 
     let op = Operation {
-        source_account: Some(MuxedAccount::Ed25519(Uint256(
-            key.verifying_key().to_bytes(),
-        ))),
+        source_account: Some(source_account.clone()),
         body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
             host_function: HostFunction::UploadContractWasm(source_code.try_into()?),
             auth: VecM::default(),
@@ -246,7 +256,7 @@ pub(crate) fn build_install_contract_code_tx(
     };
 
     let tx = Transaction {
-        source_account: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+        source_account: source_account.clone(),
         fee,
         seq_num: SequenceNumber(sequence),
         cond: Preconditions::None,
@@ -268,8 +278,12 @@ mod tests {
             b"foo",
             300,
             1,
-            &utils::parse_secret_key("SBFGFF27Y64ZUGFAIG5AMJGQODZZKV2YQKAVUUN4HNE24XZXD2OEUVUP")
-                .unwrap(),
+            &MuxedAccount::Ed25519(xdr::Uint256(
+                utils::parse_secret_key("SBFGFF27Y64ZUGFAIG5AMJGQODZZKV2YQKAVUUN4HNE24XZXD2OEUVUP")
+                    .unwrap()
+                    .verifying_key()
+                    .to_bytes(),
+            )),
         );
 
         assert!(result.is_ok());
@@ -1,22 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::{Infallible, TryInto};
 use std::ffi::OsString;
 use std::num::ParseIntError;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::{fmt::Debug, fs, io};
+use std::{
+    fmt::Debug,
+    fs,
+    io::{self, Write},
+};
 
 use clap::{arg, command, value_parser, Parser};
-use ed25519_dalek::SigningKey;
 use heck::ToKebabCase;
 
 use soroban_env_host::{
     xdr::{
         self, Hash, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, LedgerEntryData,
-        LedgerFootprint, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
-        PublicKey, ScAddress, ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScVal, ScVec,
-        SequenceNumber, SorobanAuthorizationEntry, SorobanResources, String32, StringM,
-        Transaction, TransactionExt, Uint256, VecM, WriteXdr,
+        LedgerFootprint, Memo, MuxedAccount, Operation, OperationBody, Preconditions, PublicKey,
+        ScAddress, ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScVal, ScVec, SequenceNumber,
+        SorobanAuthorizationEntry, SorobanResources, String32, StringM, Transaction,
+        TransactionExt, Uint256, VecM,
     },
     HostError,
 };
@@ -34,8 +37,10 @@ use super::super::{
 use crate::commands::txn_result::{TxnEnvelopeResult, TxnResult};
 use crate::commands::NetworkRunnable;
 use crate::{
-    commands::{config::data, global, network},
-    rpc, Pwd,
+    commands::{config::data, global, keys, network, tx},
+    rpc,
+    signer::Signer,
+    utils, Pwd,
 };
 use soroban_spec_tools::{contract, Spec};
 
@@ -43,11 +48,11 @@ use soroban_spec_tools::{contract, Spec};
 #[allow(clippy::struct_excessive_bools)]
 #[group(skip)]
 pub struct Cmd {
-    /// Contract ID to invoke
+    /// Contract ID to invoke, or its alias on the network
     #[arg(long = "id", env = "STELLAR_CONTRACT_ID")]
     pub contract_id: String,
-    // For testing only
-    #[arg(skip)]
+    /// WASM file of the contract, whose spec is used instead of fetching it from the network, e.g. to build the transaction offline with --build-only and --sequence
+    #[arg(long)]
     pub wasm: Option<std::path::PathBuf>,
     /// View the result simulating and do not sign and submit transaction
     #[arg(long, env = "STELLAR_INVOKE_VIEW")]
@@ -59,6 +64,23 @@ pub struct Cmd {
     pub config: config::Args,
     #[command(flatten)]
     pub fee: crate::fee::Args,
+    /// Channel accounts to use as the transaction source instead of the source account, e.g. `--channel-accounts chan1,chan2`. Each invocation locks one of them, so that concurrent invocations don't collide on sequence numbers. The source account still authorizes the invocation
+    #[arg(
+        long,
+        value_delimiter = ',',
+        env = "STELLAR_CHANNEL_ACCOUNTS",
+        conflicts_with_all = ["is_view", "build_only", "sim_only"]
+    )]
+    pub channel_accounts: Vec<String>,
+    /// Write the authorization entries that have to be signed by an address to this file, and print the simulated transaction instead of sending it. The entries can be signed elsewhere with `tx sign-auth`, and put back into the transaction with `tx import-auth`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["is_view", "build_only", "sim_only"])]
+    pub export_auth: Option<PathBuf>,
+    /// Print the authorizations the invocation requires, with the arguments of each call decoded using the contracts' specs
+    #[arg(long, conflicts_with = "build_only")]
+    pub preview_auth: bool,
+    /// Preview the authorizations and ask for confirmation before signing and sending the transaction
+    #[arg(long, conflicts_with_all = ["is_view", "build_only", "sim_only", "export_auth"])]
+    pub confirm: bool,
 }
 
 impl FromStr for Cmd {
@@ -118,6 +140,16 @@ pub enum Error {
     ParseIntError(#[from] ParseIntError),
     #[error(transparent)]
     Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Fee(#[from] crate::fee::Error),
+    #[error(transparent)]
+    Channel(#[from] config::channel::Error),
+    #[error(transparent)]
+    Auth(#[from] tx::auth::Error),
+    #[error("reading confirmation: {0}")]
+    CannotReadConfirmation(io::Error),
+    #[error("transaction not confirmed, it was not sent")]
+    NotConfirmed,
     #[error("unexpected contract code data type: {0:?}")]
     UnexpectedContractCodeDataType(LedgerEntryData),
     #[error("missing operation result")]
@@ -152,6 +184,8 @@ pub enum Error {
     Data(#[from] data::Error),
     #[error(transparent)]
     Network(#[from] network::Error),
+    #[error(transparent)]
+    Identity(#[from] keys::address::Error),
 }
 
 impl From<Infallible> for Error {
@@ -174,7 +208,8 @@ impl Cmd {
         contract_id: [u8; 32],
         spec_entries: &[ScSpecEntry],
         config: &config::Args,
-    ) -> Result<(String, Spec, InvokeContractArgs, Vec<SigningKey>), Error> {
+        network_passphrase: &str,
+    ) -> Result<(String, Spec, InvokeContractArgs, Vec<keys::address::Cmd>), Error> {
         let spec = Spec(Some(spec_entries.to_vec()));
         let mut cmd = clap::Command::new(self.contract_id.clone())
             .no_binary_name(true)
@@ -194,7 +229,7 @@ impl Cmd {
 
         let func = spec.find_function(function)?;
         // create parsed_args in same order as the inputs to func
-        let mut signers: Vec<SigningKey> = vec![];
+        let mut identities: Vec<keys::address::Cmd> = vec![];
         let parsed_args = func
             .inputs
             .iter()
@@ -203,16 +238,18 @@ impl Cmd {
                 if let Some(mut val) = matches_.get_raw(&name) {
                     let mut s = val.next().unwrap().to_string_lossy().to_string();
                     if matches!(i.type_, ScSpecTypeDef::Address) {
-                        let cmd = crate::commands::keys::address::Cmd {
+                        let cmd = keys::address::Cmd {
                             name: s.clone(),
                             hd_path: Some(0),
                             locator: config.locator.clone(),
                         };
-                        if let Ok(address) = cmd.public_key() {
+                        if let Some(address) = cmd.known_public_key() {
                             s = address.to_string();
-                        }
-                        if let Ok(key) = cmd.private_key() {
-                            signers.push(key);
+                            identities.push(cmd);
+                        } else if let Ok(Some(contract_id)) =
+                            config.locator.get_contract_id(&s, network_passphrase)
+                        {
+                            s = contract_id;
                         }
                     }
                     spec.from_string(&s, &i.type_)
@@ -268,13 +305,13 @@ impl Cmd {
             args: final_args,
         };
 
-        Ok((function.clone(), spec, invoke_args, signers))
+        Ok((function.clone(), spec, invoke_args, identities))
     }
 
     pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
         let res = self.invoke(global_args).await?.to_envelope();
         match res {
-            TxnEnvelopeResult::TxnEnvelope(tx) => println!("{}", tx.to_xdr_base64(Limits::none())?),
+            TxnEnvelopeResult::TxnEnvelope(tx) => self.fee.print_envelope(&tx, &self.config)?,
             TxnEnvelopeResult::Res(output) => {
                 println!("{output}");
             }
@@ -304,17 +341,87 @@ impl Cmd {
 }
 
 impl Cmd {
-    fn contract_id(&self) -> Result<[u8; 32], Error> {
-        soroban_spec_tools::utils::contract_id_from_str(&self.contract_id)
-            .map_err(|e| Error::CannotParseContractId(self.contract_id.clone(), e))
+    fn contract_id(
+        &self,
+        locator: &locator::Args,
+        network_passphrase: &str,
+    ) -> Result<[u8; 32], Error> {
+        Ok(locator.resolve_contract_id(&self.contract_id, network_passphrase)?)
+    }
+
+    /// Print the tree of authorizations of the simulated transaction. The specs of the contracts
+    /// other than the invoked one are fetched to decode their calls, falling back to raw values.
+    async fn print_auth_preview(
+        &self,
+        client: &rpc::Client,
+        tx: &Transaction,
+        contract_id: [u8; 32],
+        spec: &Spec,
+    ) {
+        let auth = tx
+            .operations
+            .iter()
+            .filter_map(|op| match &op.body {
+                OperationBody::InvokeHostFunction(body) => Some(body.auth.to_vec()),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        if auth.is_empty() {
+            eprintln!("No authorizations required");
+            return;
+        }
+        let mut specs = BTreeMap::from([(Hash(contract_id), spec.clone())]);
+        for contract in crate::log::auth_contracts(&auth) {
+            if specs.contains_key(&contract) {
+                continue;
+            }
+            match client.get_remote_contract_spec(&contract.0).await {
+                Ok(entries) => {
+                    specs.insert(contract, Spec::new(entries));
+                }
+                Err(e) => tracing::debug!("cannot fetch the spec of {contract}: {e}"),
+            }
+        }
+        eprint!("Authorizations:\n{}", crate::log::auth_tree(&auth, &specs));
     }
 }
 
+/// Signers of the auth entries of `tx` authorized by the identities passed as arguments, or by
+/// `source` when a channel account is the source of the transaction. Only the identities whose
+/// signature is needed are loaded, so that the others are not decrypted or run.
+fn auth_signers(
+    tx: &Transaction,
+    identities: &[keys::address::Cmd],
+    source: Option<&config::Args>,
+) -> Result<Vec<Signer>, Error> {
+    let accounts = utils::address_auth_entries(tx)
+        .iter()
+        .filter_map(|entry| utils::auth_entry_account(entry).map(|account| account.0))
+        .collect::<Vec<_>>();
+    let mut signers = identities
+        .iter()
+        .filter(|identity| {
+            identity
+                .known_public_key()
+                .is_some_and(|key| accounts.contains(&key.0))
+        })
+        .map(keys::address::Cmd::signer)
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(source) = source {
+        if accounts.contains(&source.public_key()?.0) {
+            signers.push(source.signer()?);
+        }
+    }
+    Ok(signers)
+}
+
 #[async_trait::async_trait]
 impl NetworkRunnable for Cmd {
     type Error = Error;
     type Result = TxnResult<String>;
 
+    #[allow(clippy::too_many_lines)]
     async fn run_against_rpc_server(
         &self,
         global_args: Option<&global::Args>,
@@ -323,73 +430,72 @@ impl NetworkRunnable for Cmd {
         let config = config.unwrap_or(&self.config);
         let network = config.get_network()?;
         tracing::trace!(?network);
-        let contract_id = self.contract_id()?;
-        let spec_entries = self.spec_entries()?;
-        if let Some(spec_entries) = &spec_entries {
-            // For testing wasm arg parsing
-            let _ = self.build_host_function_parameters(contract_id, spec_entries, config)?;
-        }
+        let contract_id = self.contract_id(&config.locator, &network.network_passphrase)?;
         let client = rpc::Client::new(&network.rpc_url)?;
-        let account_details = if self.is_view {
-            default_account_entry()
+        // Arguments are parsed before the sequence number is taken, and before any request to the
+        // network when the spec comes from --wasm
+        let spec_entries = self.spec_entries()?;
+        let spec_entries = if let Some(spec_entries) = spec_entries {
+            spec_entries
         } else {
-            client
-                .verify_network_passphrase(Some(&network.network_passphrase))
-                .await?;
-            let key = config.key_pair()?;
-
-            // Get the account sequence number
-            let public_strkey =
-                stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
-            client.get_account(&public_strkey).await?
+            fetch_spec_entries(&client, &contract_id, global_args).await?
         };
-        let sequence: i64 = account_details.seq_num.into();
-        let AccountId(PublicKey::PublicKeyTypeEd25519(account_id)) = account_details.account_id;
-
-        let r = client.get_contract_data(&contract_id).await?;
-        tracing::trace!("{r:?}");
-        let ContractDataEntry {
-            val: xdr::ScVal::ContractInstance(xdr::ScContractInstance { executable, .. }),
-            ..
-        } = r
-        else {
-            return Err(Error::MissingResult);
+        let (function, spec, host_function_params, identities) = self
+            .build_host_function_parameters(
+                contract_id,
+                &spec_entries,
+                config,
+                &network.network_passphrase,
+            )?;
+        // The lock on the channel account is held until the transaction has been sent
+        let (channel, _lock) = if self.channel_accounts.is_empty() || self.is_view {
+            (None, None)
+        } else {
+            let (channel, lock) = config::channel::acquire(&self.channel_accounts, config).await?;
+            (Some(channel), Some(lock))
         };
-        // Get the contract
-        let spec_entries = match executable {
-            xdr::ContractExecutable::Wasm(hash) => {
-                let hash = hash.to_string();
-                if let Ok(entries) = data::read_spec(&hash) {
-                    entries
-                } else {
-                    let res = client.get_remote_contract_spec(&contract_id).await?;
-                    if global_args.map_or(true, |a| !a.no_cache) {
-                        data::write_spec(&hash, &res)?;
-                    }
-                    res
-                }
-            }
-            xdr::ContractExecutable::StellarAsset => {
-                soroban_spec::read::parse_raw(&soroban_sdk::token::StellarAssetSpec::spec_xdr())?
+        let tx_source = channel.as_ref().unwrap_or(config);
+        let (sequence, source_account) = if self.is_view {
+            let AccountEntry {
+                account_id: AccountId(PublicKey::PublicKeyTypeEd25519(account_id)),
+                seq_num,
+                ..
+            } = default_account_entry();
+            (seq_num.0 + 1, MuxedAccount::Ed25519(account_id))
+        } else {
+            if !self.fee.build_only {
+                client
+                    .verify_network_passphrase(Some(&network.network_passphrase))
+                    .await?;
             }
+            (
+                self.fee.next_sequence(&client, tx_source).await?,
+                tx_source.source_muxed_account()?,
+            )
         };
 
-        // Get the ledger footprint
-        let (function, spec, host_function_params, signers) =
-            self.build_host_function_parameters(contract_id, &spec_entries, config)?;
         let tx = build_invoke_contract_tx(
             host_function_params.clone(),
-            sequence + 1,
+            sequence,
             self.fee.fee,
-            account_id,
+            source_account,
         )?;
+        let tx = self.fee.apply_to_txn(tx)?;
         if self.fee.build_only {
             return Ok(TxnResult::Txn(tx));
         }
         let txn = client.create_assembled_transaction(&tx).await?;
-        let txn = self.fee.apply_to_assembled_txn(txn);
-        if self.fee.sim_only {
-            return Ok(TxnResult::Txn(txn.transaction().clone()));
+        if self.preview_auth || self.confirm {
+            self.print_auth_preview(&client, txn.transaction(), contract_id, &spec)
+                .await;
+        }
+        if let Some(path) = &self.export_auth {
+            tx::auth::write_entries(path, &utils::address_auth_entries(txn.transaction()))?;
+        }
+        if self.export_auth.is_some() || self.fee.sim_only {
+            return Ok(TxnResult::Txn(
+                self.fee.apply_to_simulated_txn(txn.transaction().clone())?,
+            ));
         }
         let sim_res = txn.sim_response();
         if global_args.map_or(true, |a| !a.no_cache) {
@@ -404,12 +510,21 @@ impl NetworkRunnable for Cmd {
                 no_cache,
                 ..
             } = global_args.map(Clone::clone).unwrap_or_default();
-            let res = client
+            if self.confirm && !confirm()? {
+                return Err(Error::NotConfirmed);
+            }
+            let signers = auth_signers(
+                txn.transaction(),
+                &identities,
+                channel.is_some().then_some(config),
+            )?;
+            let res = self
+                .fee
                 .send_assembled_transaction(
+                    &client,
                     txn,
-                    &config.key_pair()?,
                     &signers,
-                    &network.network_passphrase,
+                    tx_source,
                     Some(log_events),
                     (verbose || very_verbose || self.fee.cost).then_some(log_resources),
                 )
@@ -425,6 +540,53 @@ impl NetworkRunnable for Cmd {
     }
 }
 
+/// Spec of a deployed contract: read from the spec cache for its wasm, or fetched from the network
+/// and cached unless `--no-cache` is set.
+pub async fn fetch_spec_entries(
+    client: &rpc::Client,
+    contract_id: &[u8; 32],
+    global_args: Option<&global::Args>,
+) -> Result<Vec<ScSpecEntry>, Error> {
+    let r = client.get_contract_data(contract_id).await?;
+    tracing::trace!("{r:?}");
+    let ContractDataEntry {
+        val: xdr::ScVal::ContractInstance(xdr::ScContractInstance { executable, .. }),
+        ..
+    } = r
+    else {
+        return Err(Error::MissingResult);
+    };
+    Ok(match executable {
+        xdr::ContractExecutable::Wasm(hash) => {
+            let hash = hash.to_string();
+            if let Ok(entries) = data::read_spec(&hash) {
+                entries
+            } else {
+                let res = client.get_remote_contract_spec(contract_id).await?;
+                if global_args.map_or(true, |a| !a.no_cache) {
+                    data::write_spec(&hash, &res)?;
+                }
+                res
+            }
+        }
+        xdr::ContractExecutable::StellarAsset => {
+            soroban_spec::read::parse_raw(&soroban_sdk::token::StellarAssetSpec::spec_xdr())?
+        }
+    })
+}
+
+fn confirm() -> Result<bool, Error> {
+    eprint!("Sign and send the transaction? [y/N] ");
+    io::stderr()
+        .flush()
+        .map_err(Error::CannotReadConfirmation)?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(Error::CannotReadConfirmation)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 const DEFAULT_ACCOUNT_ID: AccountId = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32])));
 
 fn default_account_entry() -> AccountEntry {
@@ -478,7 +640,7 @@ fn build_invoke_contract_tx(
     parameters: InvokeContractArgs,
     sequence: i64,
     fee: u32,
-    source_account_id: Uint256,
+    source_account: MuxedAccount,
 ) -> Result<Transaction, Error> {
     let op = Operation {
         source_account: None,
@@ -488,7 +650,7 @@ fn build_invoke_contract_tx(
         }),
     };
     Ok(Transaction {
-        source_account: MuxedAccount::Ed25519(source_account_id),
+        source_account,
         fee,
         seq_num: SequenceNumber(sequence),
         cond: Preconditions::None,
@@ -578,3 +740,105 @@ Each arg has a corresponding --<arg_name>-file-path which is a path to a file co
 Note: The only types which aren't JSON are Bytes and Bytes which are raw bytes"#
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use soroban_env_host::xdr::{
+        InvokeHostFunctionOp, Preconditions, ScVal, SorobanAddressCredentials,
+        SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        SorobanCredentials, TransactionExt, Uint256,
+    };
+
+    use super::*;
+    use crate::commands::config::secret::Secret;
+
+    #[test]
+    fn test_auth_signers() {
+        let dir = tempfile::tempdir().unwrap();
+        let locator = locator::Args {
+            global: false,
+            config_dir: Some(dir.path().to_path_buf()),
+        };
+        let alice = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let bob = stellar_strkey::ed25519::PublicKey([2; 32]);
+        let carol = stellar_strkey::ed25519::PublicKey([3; 32]);
+        locator
+            .write_identity(
+                "alice",
+                &stellar_strkey::ed25519::PrivateKey(alice.to_bytes()).into(),
+            )
+            .unwrap();
+        locator
+            .write_identity(
+                "bob",
+                &Secret::PublicKey {
+                    public_key: bob.to_string(),
+                },
+            )
+            .unwrap();
+        // Running this signer fails, as it does not exist
+        locator
+            .write_identity(
+                "carol",
+                &Secret::ExternalSigner {
+                    signer: "cmd:/nonexistent/signer".to_string(),
+                    public_key: Some(carol.to_string()),
+                },
+            )
+            .unwrap();
+        let identities = ["alice", "bob", "carol"].map(|name| keys::address::Cmd {
+            name: name.to_string(),
+            hd_path: Some(0),
+            locator: locator.clone(),
+        });
+        let invoke_args = InvokeContractArgs {
+            contract_address: ScAddress::Contract(Hash([4; 32])),
+            function_name: "transfer".try_into().unwrap(),
+            args: VecM::default(),
+        };
+        let tx = |account: [u8; 32]| Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([5; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function: HostFunction::InvokeContract(invoke_args.clone()),
+                    auth: vec![SorobanAuthorizationEntry {
+                        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                            address: ScAddress::Account(AccountId(
+                                PublicKey::PublicKeyTypeEd25519(Uint256(account)),
+                            )),
+                            nonce: 0,
+                            signature_expiration_ledger: 0,
+                            signature: ScVal::Void,
+                        }),
+                        root_invocation: SorobanAuthorizedInvocation {
+                            function: SorobanAuthorizedFunction::ContractFn(invoke_args.clone()),
+                            sub_invocations: VecM::default(),
+                        },
+                    }]
+                    .try_into()
+                    .unwrap(),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V0,
+        };
+
+        // Only alice signs, bob and carol are not loaded
+        let signers =
+            auth_signers(&tx(alice.verifying_key().to_bytes()), &identities, None).unwrap();
+        assert_eq!(
+            signers.iter().map(Signer::public_key).collect::<Vec<_>>(),
+            [stellar_strkey::ed25519::PublicKey(
+                alice.verifying_key().to_bytes()
+            )]
+        );
+        // A signer that cannot be loaded is an error
+        assert!(auth_signers(&tx(carol.0), &identities, None).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod alias;
 pub mod asset;
 pub mod bindings;
 pub mod build;
@@ -17,6 +18,9 @@ use crate::commands::global;
 
 #[derive(Debug, clap::Subcommand)]
 pub enum Cmd {
+    /// Name contract IDs per network, so that the names can be used in place of the IDs
+    #[command(subcommand)]
+    Alias(alias::Cmd),
     /// Utilities to deploy a Stellar Asset Contract or get its id
     #[command(subcommand)]
     Asset(asset::Cmd),
@@ -74,6 +78,9 @@ pub enum Cmd {
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[error(transparent)]
+    Alias(#[from] alias::Error),
+
     #[error(transparent)]
     Asset(#[from] asset::Error),
 
@@ -120,6 +127,7 @@ pub enum Error {
 impl Cmd {
     pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
         match &self {
+            Cmd::Alias(alias) => alias.run()?,
             Cmd::Asset(asset) => asset.run().await?,
             Cmd::Bindings(bindings) => bindings.run().await?,
             Cmd::Build(build) => build.run()?,
@@ -182,7 +182,9 @@ impl NetworkRunnable for Cmd {
         let network = config.get_network()?;
         tracing::trace!(?network);
         let client = Client::new(&network.rpc_url)?;
-        let keys = self.key.parse_keys()?;
+        let keys = self
+            .key
+            .parse_keys(&config.locator, &network.network_passphrase)?;
         Ok(client.get_full_ledger_entries(&keys).await?)
     }
 }
@@ -3,9 +3,9 @@ use std::{fmt::Debug, path::Path, str::FromStr};
 use clap::{command, Parser};
 use soroban_env_host::xdr::{
     Error as XdrError, ExtensionPoint, LedgerEntry, LedgerEntryChange, LedgerEntryData,
-    LedgerFootprint, Limits, Memo, MuxedAccount, Operation, OperationBody, OperationMeta,
-    Preconditions, RestoreFootprintOp, SequenceNumber, SorobanResources, SorobanTransactionData,
-    Transaction, TransactionExt, TransactionMeta, TransactionMetaV3, TtlEntry, Uint256, WriteXdr,
+    LedgerFootprint, Memo, Operation, OperationBody, OperationMeta, Preconditions,
+    RestoreFootprintOp, SequenceNumber, SorobanResources, SorobanTransactionData, Transaction,
+    TransactionExt, TransactionMeta, TransactionMetaV3, TtlEntry,
 };
 use stellar_strkey::DecodeError;
 
@@ -80,6 +80,8 @@ pub enum Error {
     #[error(transparent)]
     Rpc(#[from] rpc::Error),
     #[error(transparent)]
+    Fee(#[from] crate::fee::Error),
+    #[error(transparent)]
     Wasm(#[from] wasm::Error),
     #[error(transparent)]
     Key(#[from] key::Error),
@@ -97,7 +99,7 @@ impl Cmd {
         let res = self.run_against_rpc_server(None, None).await?.to_envelope();
         let expiration_ledger_seq = match res {
             TxnEnvelopeResult::TxnEnvelope(tx) => {
-                println!("{}", tx.to_xdr_base64(Limits::none())?);
+                self.fee.print_envelope(&tx, &self.config)?;
                 return Ok(());
             }
             TxnEnvelopeResult::Res(res) => res,
@@ -133,20 +135,16 @@ impl NetworkRunnable for Cmd {
         let config = config.unwrap_or(&self.config);
         let network = config.get_network()?;
         tracing::trace!(?network);
-        let entry_keys = self.key.parse_keys()?;
+        let entry_keys = self
+            .key
+            .parse_keys(&config.locator, &network.network_passphrase)?;
         let client = Client::new(&network.rpc_url)?;
-        let key = config.key_pair()?;
-
-        // Get the account sequence number
-        let public_strkey =
-            stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
-        let account_details = client.get_account(&public_strkey).await?;
-        let sequence: i64 = account_details.seq_num.into();
+        let sequence = self.fee.next_sequence(&client, config).await?;
 
         let tx = Transaction {
-            source_account: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+            source_account: config.source_muxed_account()?,
             fee: self.fee.fee,
-            seq_num: SequenceNumber(sequence + 1),
+            seq_num: SequenceNumber(sequence),
             cond: Preconditions::None,
             memo: Memo::None,
             operations: vec![Operation {
@@ -170,11 +168,14 @@ impl NetworkRunnable for Cmd {
                 resource_fee: 0,
             }),
         };
+        let tx = self.fee.apply_to_txn(tx)?;
         if self.fee.build_only {
             return Ok(TxnResult::Txn(tx));
         }
-        let res = client
-            .prepare_and_send_transaction(&tx, &key, &[], &network.network_passphrase, None, None)
+        let txn = client.create_assembled_transaction(&tx).await?;
+        let res = self
+            .fee
+            .send_assembled_transaction(&client, txn, &[], config, None, None)
             .await?;
         if args.map_or(true, |a| !a.no_cache) {
             data::write(res.clone().try_into()?, &network.rpc_uri()?)?;
@@ -31,7 +31,8 @@ pub struct Cmd {
     count: usize,
     /// A set of (up to 5) contract IDs to filter events on. This parameter can
     /// be passed multiple times, e.g. `--id C123.. --id C456..`, or passed with
-    /// multiple parameters, e.g. `--id C123 C456`.
+    /// multiple parameters, e.g. `--id C123 C456`. Contract aliases on the
+    /// network are accepted in place of IDs.
     ///
     /// Though the specification supports multiple filter objects (i.e.
     /// combinations of type, IDs, and topics), only one set can be specified on
@@ -159,14 +160,6 @@ impl Cmd {
             }
         }
 
-        // Validate contract_ids
-        for id in &mut self.contract_ids {
-            utils::contract_id_from_str(id).map_err(|e| Error::InvalidContractId {
-                contract_id: id.clone(),
-                error: e,
-            })?;
-        }
-
         let response = self.run_against_rpc_server(None, None).await?;
 
         for event in &response.events {
@@ -222,6 +215,30 @@ impl NetworkRunnable for Cmd {
             self.network.get(&self.locator)
         }?;
 
+        // Validate contract_ids, replacing aliases with the contracts they name on the network
+        let contract_ids = self
+            .contract_ids
+            .iter()
+            .map(|id| {
+                if utils::exact_contract_id_from_str(id).is_some() {
+                    return Ok(id.clone());
+                }
+                if let Some(contract_id) = self
+                    .locator
+                    .get_contract_id(id, &network.network_passphrase)?
+                {
+                    return Ok(contract_id);
+                }
+                match utils::contract_id_from_str(id) {
+                    Ok(_) => Ok(id.clone()),
+                    Err(error) => Err(Error::InvalidContractId {
+                        contract_id: id.clone(),
+                        error,
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
         let client = rpc::Client::new(&network.rpc_url)?;
         client
             .verify_network_passphrase(Some(&network.network_passphrase))
@@ -230,7 +247,7 @@ impl NetworkRunnable for Cmd {
             .get_events(
                 start,
                 Some(self.event_type),
-                &self.contract_ids,
+                &contract_ids,
                 &self.topic_filters,
                 Some(self.count),
             )
@@ -1,6 +1,6 @@
-use clap::command;
+use clap::{arg, command};
 
-use super::super::config::{locator, secret};
+use super::super::config::{encryption, locator, secret};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -9,6 +9,9 @@ pub enum Error {
 
     #[error(transparent)]
     Config(#[from] locator::Error),
+
+    #[error(transparent)]
+    Encryption(#[from] encryption::Error),
 }
 
 #[derive(Debug, clap::Parser, Clone)]
@@ -20,14 +23,26 @@ pub struct Cmd {
     #[command(flatten)]
     pub secrets: secret::Args,
 
+    /// Mux id of the identity, making it a muxed sub-account of the secret's account
+    #[arg(long)]
+    pub mux_id: Option<u64>,
+
+    /// Encrypt the secret with a passphrase, read from `STELLAR_IDENTITY_PASSPHRASE` or prompted for. It is then asked for whenever the identity signs
+    #[arg(long, conflicts_with_all = ["signer", "secret_env", "public_key", "mnemonic_passphrase"])]
+    pub encrypt: bool,
+
     #[command(flatten)]
     pub config_locator: locator::Args,
 }
 
 impl Cmd {
     pub fn run(&self) -> Result<(), Error> {
+        let mut secret = self.secrets.read_secret()?;
+        if self.encrypt {
+            secret = secret.encrypt(&encryption::passphrase(true)?)?;
+        }
         Ok(self
             .config_locator
-            .write_identity(&self.name, &self.secrets.read_secret()?)?)
+            .write_muxed_identity(&self.name, &secret, self.mux_id)?)
     }
 }
@@ -1,4 +1,7 @@
-use crate::commands::config::secret;
+use crate::{
+    commands::config::secret::{self, Secret},
+    signer::Signer,
+};
 
 use super::super::config::locator;
 use clap::arg;
@@ -31,24 +34,62 @@ pub struct Cmd {
 
 impl Cmd {
     pub fn run(&self) -> Result<(), Error> {
-        println!("{}", self.public_key()?);
+        let public_key = self.public_key()?;
+        if let Ok(Some(id)) = self.locator.read_identity_mux_id(&self.name) {
+            let muxed = stellar_strkey::ed25519::MuxedAccount {
+                ed25519: public_key.0,
+                id,
+            };
+            println!("{muxed}");
+        } else {
+            println!("{public_key}");
+        }
         Ok(())
     }
 
     pub fn private_key(&self) -> Result<ed25519_dalek::SigningKey, Error> {
-        Ok(self
-            .locator
-            .read_identity(&self.name)?
-            .key_pair(self.hd_path)?)
+        Ok(self.secret()?.key_pair(self.hd_path)?)
+    }
+
+    /// Signer of the identity, which may be an external signer.
+    pub fn signer(&self) -> Result<Signer, Error> {
+        Ok(self.secret()?.signer(self.hd_path)?)
+    }
+
+    fn secret(&self) -> Result<Secret, Error> {
+        Ok(
+            if let Ok(muxed) = stellar_strkey::ed25519::MuxedAccount::from_string(&self.name) {
+                self.locator.identity_for_public_key(
+                    &stellar_strkey::ed25519::PublicKey(muxed.ed25519),
+                    self.hd_path,
+                )?
+            } else {
+                self.locator.read_identity(&self.name)?
+            },
+        )
+    }
+
+    /// Account ID of the identity when it is known without running its external signer or
+    /// prompting for a passphrase, or of the address itself.
+    pub fn known_public_key(&self) -> Option<stellar_strkey::ed25519::PublicKey> {
+        if let Ok(key) = stellar_strkey::ed25519::PublicKey::from_string(&self.name) {
+            Some(key)
+        } else if let Ok(muxed) = stellar_strkey::ed25519::MuxedAccount::from_string(&self.name) {
+            Some(stellar_strkey::ed25519::PublicKey(muxed.ed25519))
+        } else {
+            self.secret().ok()?.known_public_key(self.hd_path)
+        }
     }
 
+    /// Account ID of the identity, or of the address itself. The mux id of an M-address is
+    /// dropped, as contract addresses cannot be muxed.
     pub fn public_key(&self) -> Result<stellar_strkey::ed25519::PublicKey, Error> {
         if let Ok(key) = stellar_strkey::ed25519::PublicKey::from_string(&self.name) {
             Ok(key)
+        } else if let Ok(muxed) = stellar_strkey::ed25519::MuxedAccount::from_string(&self.name) {
+            Ok(stellar_strkey::ed25519::PublicKey(muxed.ed25519))
         } else {
-            Ok(stellar_strkey::ed25519::PublicKey::from_payload(
-                self.private_key()?.verifying_key().as_bytes(),
-            )?)
+            Ok(self.secret()?.public_key(self.hd_path)?)
         }
     }
 }
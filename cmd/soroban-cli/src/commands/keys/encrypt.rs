@@ -0,0 +1,35 @@
+use clap::command;
+
+use super::super::config::{encryption, locator};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] locator::Error),
+
+    #[error(transparent)]
+    Encryption(#[from] encryption::Error),
+}
+
+#[derive(Debug, clap::Parser, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Names of the identities to encrypt. Default: all the local and global identities
+    pub names: Vec<String>,
+
+    #[command(flatten)]
+    pub config_locator: locator::Args,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        let passphrase = encryption::passphrase(true)?;
+        for name in self
+            .config_locator
+            .encrypt_identities(&self.names, &passphrase)?
+        {
+            eprintln!("Encrypted {name}");
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,17 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
 use clap::{arg, command};
+use rand::RngCore;
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
 
 use crate::commands::network;
 
 use super::super::config::{
-    locator,
+    encryption, locator,
     secret::{self, Secret},
 };
 
@@ -15,9 +23,16 @@ pub enum Error {
     Secret(#[from] secret::Error),
     #[error(transparent)]
     Network(#[from] network::Error),
+    #[error(transparent)]
+    Encryption(#[from] encryption::Error),
+    #[error("vanity pattern {0:?} can only contain the characters A-Z and 2-7")]
+    InvalidVanityPattern(String),
+    #[error("vanity prefix {0:?} cannot be matched, the character after the leading G is one of A, B, C or D")]
+    UnmatchableVanityPrefix(String),
 }
 
 #[derive(Debug, clap::Parser, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 #[group(skip)]
 pub struct Cmd {
     /// Name of identity
@@ -41,30 +56,86 @@ pub struct Cmd {
     #[arg(long)]
     pub hd_path: Option<usize>,
 
+    /// Number of words of the random seed phrase
+    #[arg(long, value_enum, default_value = "12", conflicts_with_all = ["seed", "default_seed", "vanity_prefix", "vanity_suffix"])]
+    pub words: Words,
+
     /// Generate the default seed phrase. Useful for testing.
     /// Equivalent to --seed 0000000000000000
     #[arg(long, short = 'd', conflicts_with = "seed")]
     pub default_seed: bool,
 
+    /// Mux id of the identity, making it a muxed sub-account of the generated account
+    #[arg(long)]
+    pub mux_id: Option<u64>,
+
+    /// Encrypt the secret with a passphrase, read from `STELLAR_IDENTITY_PASSPHRASE` or prompted for. It is then asked for whenever the identity signs
+    #[arg(long)]
+    pub encrypt: bool,
+
+    /// Search random keys for an address starting with these characters after its leading G, e.g. `--vanity-prefix ISSUER`. The search runs on all cores, and takes about 32 times longer for every additional character
+    #[arg(long, conflicts_with_all = ["seed", "default_seed", "hd_path"])]
+    pub vanity_prefix: Option<String>,
+
+    /// Search random keys for an address ending with these characters
+    #[arg(long, conflicts_with_all = ["seed", "default_seed", "hd_path"])]
+    pub vanity_suffix: Option<String>,
+
     #[command(flatten)]
     pub network: network::Args,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Words {
+    /// 12 words, from 128 bits of entropy
+    #[default]
+    #[value(name = "12")]
+    Twelve,
+    /// 24 words, from 256 bits of entropy
+    #[value(name = "24")]
+    TwentyFour,
+}
+
+impl From<Words> for sep5::MnemonicType {
+    fn from(words: Words) -> Self {
+        match words {
+            Words::Twelve => sep5::MnemonicType::Words12,
+            Words::TwentyFour => sep5::MnemonicType::Words24,
+        }
+    }
+}
+
 impl Cmd {
     pub async fn run(&self) -> Result<(), Error> {
-        let seed_phrase = if self.default_seed {
-            Secret::test_seed_phrase()
-        } else {
-            Secret::from_seed(self.seed.as_deref())
-        }?;
-        let secret = if self.as_secret {
-            seed_phrase.private_key(self.hd_path)?.into()
+        let secret = if self.vanity_prefix.is_some() || self.vanity_suffix.is_some() {
+            let prefix = vanity_pattern(self.vanity_prefix.as_deref())?;
+            let suffix = vanity_pattern(self.vanity_suffix.as_deref())?;
+            if prefix.starts_with(|c| !matches!(c, 'A'..='D')) {
+                return Err(Error::UnmatchableVanityPrefix(prefix));
+            }
+            vanity_search(&prefix, &suffix).into()
         } else {
-            seed_phrase
+            let seed_phrase = if self.default_seed {
+                Secret::test_seed_phrase()
+            } else {
+                Secret::from_seed(self.seed.as_deref(), self.words.into())
+            }?;
+            if self.as_secret {
+                seed_phrase.private_key(self.hd_path)?.into()
+            } else {
+                seed_phrase
+            }
         };
-        self.config_locator.write_identity(&self.name, &secret)?;
+        let addr = secret.public_key(self.hd_path)?;
+        if self.encrypt {
+            let encrypted = secret.encrypt(&encryption::passphrase(true)?)?;
+            self.config_locator
+                .write_muxed_identity(&self.name, &encrypted, self.mux_id)?;
+        } else {
+            self.config_locator
+                .write_muxed_identity(&self.name, &secret, self.mux_id)?;
+        }
         if !self.no_fund {
-            let addr = secret.public_key(self.hd_path)?;
             let network = self.network.get(&self.config_locator)?;
             network
                 .fund_address(&addr)
@@ -77,3 +148,116 @@ impl Cmd {
         Ok(())
     }
 }
+
+fn vanity_pattern(pattern: Option<&str>) -> Result<String, Error> {
+    let pattern = pattern.unwrap_or_default().to_uppercase();
+    if pattern.chars().all(|c| matches!(c, 'A'..='Z' | '2'..='7')) {
+        Ok(pattern)
+    } else {
+        Err(Error::InvalidVanityPattern(pattern))
+    }
+}
+
+/// Number of random keys to try, on average, to find an address matching `prefix` and `suffix`.
+/// Every character matches one in 32 addresses, except the first one of the prefix which can
+/// only be one of 4.
+fn expected_attempts(prefix: &str, suffix: &str) -> f64 {
+    let prefix = if prefix.is_empty() {
+        1.0
+    } else {
+        4.0 * 32f64.powi(i32::try_from(prefix.len()).unwrap_or(i32::MAX) - 1)
+    };
+    prefix * 32f64.powi(i32::try_from(suffix.len()).unwrap_or(i32::MAX))
+}
+
+fn address(seed: &[u8; 32]) -> String {
+    PublicKey(
+        ed25519_dalek::SigningKey::from_bytes(seed)
+            .verifying_key()
+            .to_bytes(),
+    )
+    .to_string()
+}
+
+fn matches_vanity(address: &str, prefix: &str, suffix: &str) -> bool {
+    address[1..].starts_with(prefix) && address.ends_with(suffix)
+}
+
+/// Search random keys on all cores for one whose address matches `prefix` and `suffix`, reporting
+/// the progress of the search.
+fn vanity_search(prefix: &str, suffix: &str) -> PrivateKey {
+    let expected = expected_attempts(prefix, suffix);
+    let threads = thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    eprintln!(
+        "Searching for an address matching G{prefix}…{suffix} on {threads} threads, expecting to try {expected:.0} keys"
+    );
+    let start = Instant::now();
+    let attempts = AtomicU64::new(0);
+    let found = AtomicBool::new(false);
+    let key = thread::scope(|scope| {
+        let searches = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut rng = rand::thread_rng();
+                    let mut seed = [0; 32];
+                    while !found.load(Ordering::Relaxed) {
+                        rng.fill_bytes(&mut seed);
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        if matches_vanity(&address(&seed), prefix, suffix) {
+                            found.store(true, Ordering::Relaxed);
+                            return Some(PrivateKey(seed));
+                        }
+                    }
+                    None
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut last_report = Instant::now();
+        while !found.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+            if last_report.elapsed() >= Duration::from_secs(5) {
+                last_report = Instant::now();
+                #[allow(clippy::cast_precision_loss)]
+                let tried = attempts.load(Ordering::Relaxed) as f64;
+                let rate = tried / start.elapsed().as_secs_f64();
+                eprintln!(
+                    "Tried {tried:.0} keys at {rate:.0} keys/s, expected time: {:.0}s",
+                    expected / rate
+                );
+            }
+        }
+        searches
+            .into_iter()
+            .find_map(|search| search.join().ok().flatten())
+    })
+    .expect("a search found a key");
+    let elapsed = start.elapsed().as_secs_f64();
+    #[allow(clippy::cast_precision_loss)]
+    let tried = attempts.load(Ordering::Relaxed) as f64;
+    eprintln!(
+        "Found {} after trying {tried:.0} keys in {elapsed:.1}s ({:.0} keys/s)",
+        address(&key.0),
+        tried / elapsed
+    );
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vanity_search() {
+        assert_eq!(vanity_pattern(Some("ab7")).unwrap(), "AB7");
+        assert!(matches!(
+            vanity_pattern(Some("A1")),
+            Err(Error::InvalidVanityPattern(_))
+        ));
+        assert!((expected_attempts("", "") - 1.0).abs() < f64::EPSILON);
+        assert!((expected_attempts("AB", "X") - 4096.0).abs() < f64::EPSILON);
+
+        let key = vanity_search("A", "Z");
+        let address = address(&key.0);
+        assert!(address.starts_with("GA") && address.ends_with('Z'));
+    }
+}
@@ -1,11 +1,25 @@
-use clap::command;
+use clap::{arg, command};
+use serde::Serialize;
 
-use super::super::config::locator;
+use super::super::config::{
+    locator::{self, KeyType, Location},
+    secret::Secret,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error(transparent)]
     Config(#[from] locator::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Names, or a report per identity with --long
+    Text,
+    /// JSON array with an object per identity
+    Json,
 }
 
 #[derive(Debug, clap::Parser, Clone)]
@@ -14,13 +28,38 @@ pub struct Cmd {
     #[command(flatten)]
     pub config_locator: locator::Args,
 
+    /// List the address, secret type, hd path and location of each identity
     #[arg(long, short = 'l')]
     pub long: bool,
+
+    /// Format of the list
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Identity as listed. Only what can be known without decrypting its secret is listed, and
+/// external signers are not run.
+#[derive(Debug, Serialize)]
+pub struct Identity {
+    pub name: String,
+    /// Address of the identity, at hd path 0 for seed phrases
+    pub address: Option<String>,
+    pub secret_type: &'static str,
+    pub backend: String,
+    /// Hd path of the address, for seed phrases
+    pub hd_path: Option<usize>,
+    /// Whether the identity is in the local or the global config
+    pub location: &'static str,
+    pub path: String,
 }
 
 impl Cmd {
     pub fn run(&self) -> Result<(), Error> {
-        let res = if self.long { self.ls_l() } else { self.ls() }?.join("\n");
+        let res = match self.output {
+            OutputFormat::Json => serde_json::to_string_pretty(&self.identities()?)?,
+            OutputFormat::Text if self.long => self.ls_l()?.join("\n"),
+            OutputFormat::Text => self.ls()?.join("\n"),
+        };
         println!("{res}");
         Ok(())
     }
@@ -32,10 +71,67 @@ impl Cmd {
 
     pub fn ls_l(&self) -> Result<Vec<String>, Error> {
         Ok(self
-            .config_locator
-            .list_identities_long()?
+            .identities()?
             .into_iter()
-            .map(|(name, location)| format!("{location}\nName: {name}\n"))
-            .collect::<Vec<String>>())
+            .map(|identity| {
+                let Identity {
+                    name,
+                    address,
+                    secret_type,
+                    backend,
+                    hd_path,
+                    location,
+                    path,
+                } = identity;
+                let address = address.unwrap_or_else(|| "unknown".to_string());
+                let hd_path = hd_path.map_or_else(|| "none".to_string(), |i| i.to_string());
+                format!(
+                    "{path}\nName: {name}\nBackend: {backend}\nAddress: {address}\nType: {secret_type}\nHD path: {hd_path}\nLocation: {location}\n"
+                )
+            })
+            .collect())
+    }
+
+    pub fn identities(&self) -> Result<Vec<Identity>, Error> {
+        Ok(KeyType::Identity
+            .list_paths(&self.config_locator.local_and_global()?)?
+            .into_iter()
+            .map(|(name, location)| {
+                let secret = KeyType::read_from_path::<Secret>(location.as_ref()).ok();
+                Identity {
+                    name,
+                    address: secret
+                        .as_ref()
+                        .and_then(|secret| secret.known_public_key(None))
+                        .map(|key| key.to_string()),
+                    secret_type: secret.as_ref().map_or("unknown", secret_type),
+                    backend: secret.as_ref().map_or_else(
+                        || "unknown".to_string(),
+                        |secret| secret.backend().kind().to_string(),
+                    ),
+                    hd_path: matches!(
+                        secret,
+                        Some(Secret::SeedPhrase { .. } | Secret::SeedPhraseWithPassphrase { .. })
+                    )
+                    .then_some(0),
+                    path: location.as_ref().display().to_string(),
+                    location: match location {
+                        Location::Local(_) => "local",
+                        Location::Global(_) => "global",
+                    },
+                }
+            })
+            .collect())
+    }
+}
+
+fn secret_type(secret: &Secret) -> &'static str {
+    match secret {
+        Secret::SecretKey { .. } => "secret key",
+        Secret::SeedPhrase { .. } => "seed phrase",
+        Secret::SeedPhraseWithPassphrase { .. } => "seed phrase with passphrase",
+        Secret::ExternalSigner { .. } => "external",
+        Secret::PublicKey { .. } => "watch-only",
+        Secret::Encrypted { .. } | Secret::EnvVar { .. } => "secret key or seed phrase",
     }
 }
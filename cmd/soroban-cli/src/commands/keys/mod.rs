@@ -2,6 +2,7 @@ use clap::Parser;
 
 pub mod add;
 pub mod address;
+pub mod encrypt;
 pub mod fund;
 pub mod generate;
 pub mod ls;
@@ -14,6 +15,8 @@ pub enum Cmd {
     Add(add::Cmd),
     /// Given an identity return its address (public key)
     Address(address::Cmd),
+    /// Encrypt the secrets of existing identities with a passphrase, replacing their plaintext secret keys and seed phrases
+    Encrypt(encrypt::Cmd),
     /// Fund an identity on a test network
     Fund(fund::Cmd),
     /// Generate a new identity with a seed phrase, currently 12 words
@@ -34,6 +37,8 @@ pub enum Error {
     #[error(transparent)]
     Address(#[from] address::Error),
     #[error(transparent)]
+    Encrypt(#[from] encrypt::Error),
+    #[error(transparent)]
     Fund(#[from] fund::Error),
 
     #[error(transparent)]
@@ -52,6 +57,7 @@ impl Cmd {
         match self {
             Cmd::Add(cmd) => cmd.run()?,
             Cmd::Address(cmd) => cmd.run()?,
+            Cmd::Encrypt(cmd) => cmd.run()?,
             Cmd::Fund(cmd) => cmd.run().await?,
             Cmd::Generate(cmd) => cmd.run().await?,
             Cmd::Ls(cmd) => cmd.run()?,
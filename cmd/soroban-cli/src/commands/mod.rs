@@ -12,6 +12,7 @@ pub mod global;
 pub mod keys;
 pub mod network;
 pub mod plugin;
+pub mod tx;
 pub mod version;
 
 pub mod txn_result;
@@ -101,6 +102,7 @@ impl Root {
             Cmd::Version(version) => version.run(),
             Cmd::Keys(id) => id.run().await?,
             Cmd::Cache(data) => data.run()?,
+            Cmd::Tx(tx) => tx.run(&self.global_args).await?,
         };
         Ok(())
     }
@@ -115,6 +117,7 @@ impl FromStr for Root {
 }
 
 #[derive(Parser, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Cmd {
     /// Print shell completion code for the specified shell.
     #[command(long_about = completion::LONG_ABOUT)]
@@ -137,6 +140,9 @@ pub enum Cmd {
     /// Cache for tranasctions and contract specs
     #[command(subcommand)]
     Cache(cache::Cmd),
+    /// Sign, simulate, and send transactions
+    #[command(subcommand)]
+    Tx(tx::Cmd),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -158,6 +164,8 @@ pub enum Error {
     Network(#[from] network::Error),
     #[error(transparent)]
     Cache(#[from] cache::Error),
+    #[error(transparent)]
+    Tx(#[from] tx::Error),
 }
 
 #[async_trait]
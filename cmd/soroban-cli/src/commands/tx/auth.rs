@@ -0,0 +1,44 @@
+use std::{fs, io, path::Path};
+
+use soroban_env_host::xdr::{self, Limits, ReadXdr, SorobanAuthorizationEntry, WriteXdr};
+
+use super::xdr::read;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Read(#[from] super::xdr::Error),
+    #[error("writing authorization entries to {0:?}: {1}")]
+    CannotWriteFile(std::path::PathBuf, io::Error),
+    #[error("decoding authorization entry: {0}")]
+    Xdr(#[from] xdr::Error),
+}
+
+/// Read the authorization entries of `path`, or of stdin if it is `None` or `-`. Entries are
+/// base64 encoded `SorobanAuthorizationEntry`s, one per line.
+pub fn read_entries(path: Option<&Path>) -> Result<Vec<SorobanAuthorizationEntry>, Error> {
+    read(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            Ok(SorobanAuthorizationEntry::from_xdr_base64(
+                line,
+                Limits::none(),
+            )?)
+        })
+        .collect()
+}
+
+pub fn to_lines(entries: &[SorobanAuthorizationEntry]) -> Result<String, Error> {
+    Ok(entries
+        .iter()
+        .map(|entry| entry.to_xdr_base64(Limits::none()))
+        .collect::<Result<Vec<_>, _>>()?
+        .join("\n"))
+}
+
+pub fn write_entries(path: &Path, entries: &[SorobanAuthorizationEntry]) -> Result<(), Error> {
+    fs::write(path, to_lines(entries)? + "\n")
+        .map_err(|e| Error::CannotWriteFile(path.to_path_buf(), e))
+}
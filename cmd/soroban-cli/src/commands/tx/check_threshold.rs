@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use clap::{command, Parser};
+use soroban_env_host::xdr::{
+    self, AccountEntry, AccountId, DecoratedSignature, MuxedAccount, PublicKey, Signer, SignerKey,
+    TransactionEnvelope, Uint256,
+};
+
+use crate::{
+    commands::{
+        config::{self, locator},
+        global, network, tx, NetworkRunnable,
+    },
+    rpc::{self, Client},
+    utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error("signature weight {weight} does not meet the {level} threshold of {threshold}")]
+    ThresholdNotMet {
+        weight: u32,
+        threshold: u8,
+        level: Threshold,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum Threshold {
+    /// Low threshold, e.g. bump sequence and allow trust
+    Low,
+    /// Medium threshold, used by most operations including contract invocations
+    Medium,
+    /// High threshold, e.g. account merge and changing signers or thresholds
+    High,
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Threshold::Low => "low",
+                Threshold::Medium => "medium",
+                Threshold::High => "high",
+            }
+        )
+    }
+}
+
+impl Threshold {
+    pub fn of(self, account: &AccountEntry) -> u8 {
+        let [_, low, medium, high] = account.thresholds.0;
+        match self {
+            Threshold::Low => low,
+            Threshold::Medium => medium,
+            Threshold::High => high,
+        }
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    /// Threshold of the source account the signatures must meet
+    #[arg(long, value_enum, default_value = "medium")]
+    pub threshold: Threshold,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+/// Signers of the source account which signed the envelope, with the threshold required.
+pub struct Weights {
+    pub source: MuxedAccount,
+    pub signers: Vec<(stellar_strkey::ed25519::PublicKey, u32)>,
+    pub threshold: u8,
+}
+
+impl Weights {
+    pub fn total(&self) -> u32 {
+        self.signers.iter().map(|(_, weight)| weight).sum()
+    }
+
+    pub fn is_met(&self) -> bool {
+        // The network always requires at least one valid signature, even with a threshold of 0.
+        self.total() >= u32::from(self.threshold).max(1)
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self) -> Result<(), Error> {
+        let weights = self.run_against_rpc_server(None, None).await?;
+        println!(
+            "Source account: {}",
+            utils::muxed_account_to_string(&weights.source)
+        );
+        for (signer, weight) in &weights.signers {
+            println!("{signer}: {weight}");
+        }
+        if !weights.is_met() {
+            return Err(Error::ThresholdNotMet {
+                weight: weights.total(),
+                threshold: weights.threshold,
+                level: self.threshold,
+            });
+        }
+        println!(
+            "Signature weight {} meets the {} threshold of {}",
+            weights.total(),
+            self.threshold,
+            weights.threshold
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NetworkRunnable for Cmd {
+    type Error = Error;
+    type Result = Weights;
+
+    async fn run_against_rpc_server(
+        &self,
+        _: Option<&global::Args>,
+        config: Option<&config::Args>,
+    ) -> Result<Weights, Error> {
+        let network = if let Some(config) = config {
+            config.get_network()?
+        } else {
+            self.network.get(&self.locator)?
+        };
+        let tx_env = self.tx_xdr.envelope()?;
+        let client = Client::new(&network.rpc_url)?;
+        let source = source_account(&tx_env);
+        let account_id =
+            stellar_strkey::ed25519::PublicKey(utils::muxed_account_ed25519(&source).0);
+        let account = client.get_account(&account_id.to_string()).await?;
+        let tx_hash = utils::transaction_envelope_hash(&tx_env, &network.network_passphrase)?;
+        Ok(Weights {
+            source,
+            signers: signer_weights(&account, utils::envelope_signatures(&tx_env), &tx_hash),
+            threshold: self.threshold.of(&account),
+        })
+    }
+}
+
+/// Account whose thresholds apply to the envelope's signatures.
+pub fn source_account(tx_env: &TransactionEnvelope) -> MuxedAccount {
+    match tx_env {
+        TransactionEnvelope::TxV0(env) => {
+            MuxedAccount::Ed25519(env.tx.source_account_ed25519.clone())
+        }
+        TransactionEnvelope::Tx(env) => env.tx.source_account.clone(),
+        TransactionEnvelope::TxFeeBump(env) => env.tx.fee_source.clone(),
+    }
+}
+
+/// Signers of `account`, including its master key, with a valid signature over `tx_hash`.
+pub fn signer_weights(
+    account: &AccountEntry,
+    signatures: &[DecoratedSignature],
+    tx_hash: &[u8; 32],
+) -> Vec<(stellar_strkey::ed25519::PublicKey, u32)> {
+    let AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(master))) = &account.account_id;
+    let master_weight = u32::from(account.thresholds.0[0]);
+    std::iter::once((*master, master_weight))
+        .chain(account.signers.iter().filter_map(|Signer { key, weight }| {
+            if let SignerKey::Ed25519(Uint256(key)) = key {
+                Some((*key, *weight))
+            } else {
+                None
+            }
+        }))
+        .filter(|(key, weight)| *weight > 0 && has_signed(key, signatures, tx_hash))
+        .map(|(key, weight)| (stellar_strkey::ed25519::PublicKey(key), weight))
+        .collect()
+}
+
+fn has_signed(key: &[u8; 32], signatures: &[DecoratedSignature], tx_hash: &[u8; 32]) -> bool {
+    let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_bytes(key) else {
+        return false;
+    };
+    signatures
+        .iter()
+        .filter(|s| s.hint.0 == key[28..])
+        .filter_map(|s| <[u8; 64]>::try_from(s.signature.as_slice()).ok())
+        .any(|s| {
+            verifying_key
+                .verify_strict(tx_hash, &ed25519_dalek::Signature::from_bytes(&s))
+                .is_ok()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_env_host::xdr::{
+        AccountEntryExt, SequenceNumber, SignatureHint, String32, StringM, Thresholds, VecM,
+    };
+
+    fn account(
+        master: &ed25519_dalek::SigningKey,
+        signers: &[(&ed25519_dalek::SigningKey, u32)],
+    ) -> AccountEntry {
+        AccountEntry {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                master.verifying_key().to_bytes(),
+            ))),
+            balance: 0,
+            seq_num: SequenceNumber(0),
+            num_sub_entries: 0,
+            inflation_dest: None,
+            flags: 0,
+            home_domain: String32::from(StringM::default()),
+            thresholds: Thresholds([1, 1, 2, 3]),
+            signers: signers
+                .iter()
+                .map(|(key, weight)| Signer {
+                    key: SignerKey::Ed25519(Uint256(key.verifying_key().to_bytes())),
+                    weight: *weight,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            ext: AccountEntryExt::V0,
+        }
+    }
+
+    fn decorated_signature(key: &ed25519_dalek::SigningKey, hash: &[u8; 32]) -> DecoratedSignature {
+        use ed25519_dalek::Signer as _;
+        DecoratedSignature {
+            hint: SignatureHint(key.verifying_key().to_bytes()[28..].try_into().unwrap()),
+            signature: xdr::Signature(key.sign(hash).to_bytes().try_into().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_signer_weights() {
+        let master = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let alice = ed25519_dalek::SigningKey::from_bytes(&[2; 32]);
+        let bob = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let account = account(&master, &[(&alice, 1), (&bob, 1)]);
+        let tx_hash = [7; 32];
+        let other_hash = [8; 32];
+        let signatures: VecM<DecoratedSignature, 20> = vec![
+            decorated_signature(&alice, &tx_hash),
+            decorated_signature(&bob, &other_hash),
+            decorated_signature(&master, &tx_hash),
+        ]
+        .try_into()
+        .unwrap();
+
+        let weights = Weights {
+            source: MuxedAccount::Ed25519(Uint256(master.verifying_key().to_bytes())),
+            signers: signer_weights(&account, &signatures, &tx_hash),
+            threshold: Threshold::Medium.of(&account),
+        };
+        assert_eq!(weights.signers.len(), 2);
+        assert_eq!(weights.total(), 2);
+        assert!(weights.is_met());
+        let weights = Weights {
+            threshold: Threshold::High.of(&account),
+            ..weights
+        };
+        assert!(!weights.is_met());
+    }
+}
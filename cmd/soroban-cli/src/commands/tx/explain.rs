@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use clap::{command, Parser};
+use soroban_env_host::xdr::{
+    self, ContractExecutable, ContractIdPreimage, CreateContractArgs, FeeBumpTransactionInnerTx,
+    Hash, HostFunction, InvokeContractArgs, Operation, OperationBody, Preconditions, ScAddress,
+    TimeBounds, Transaction, TransactionEnvelope, TransactionExt,
+};
+use soroban_spec_tools::Spec;
+
+use crate::{
+    commands::{config::locator, contract::invoke, global, network, tx},
+    memo, rpc, utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        let tx_env = self.tx_xdr.envelope()?;
+        let network = self.network.get(&self.locator)?;
+        let client = rpc::Client::new(&network.rpc_url)?;
+        let mut specs = BTreeMap::new();
+        for contract in contracts(&transaction(&tx_env)) {
+            match invoke::fetch_spec_entries(&client, &contract.0, Some(global_args)).await {
+                Ok(entries) => {
+                    specs.insert(contract, Spec::new(entries));
+                }
+                Err(e) => tracing::debug!("cannot fetch the spec of {contract}: {e}"),
+            }
+        }
+        print!("{}", explain(&tx_env, &network.network_passphrase, &specs)?);
+        Ok(())
+    }
+}
+
+/// Readable summary of `tx_env`, with the arguments of contract calls named and typed from the
+/// contracts' `specs` when available.
+pub fn explain(
+    tx_env: &TransactionEnvelope,
+    network_passphrase: &str,
+    specs: &BTreeMap<Hash, Spec>,
+) -> Result<String, Error> {
+    let mut out = String::new();
+    let tx = transaction(tx_env);
+    let _ = writeln!(
+        out,
+        "Hash: {}",
+        hex::encode(utils::transaction_envelope_hash(
+            tx_env,
+            network_passphrase
+        )?)
+    );
+    if let TransactionEnvelope::TxFeeBump(env) = tx_env {
+        let _ = writeln!(
+            out,
+            "Fee bump: {} stroops paid by {}",
+            env.tx.fee,
+            utils::muxed_account_to_string(&env.tx.fee_source)
+        );
+        let _ = writeln!(
+            out,
+            "Inner transaction hash: {}",
+            hex::encode(utils::transaction_hash(&tx, network_passphrase)?)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "Source: {}",
+        utils::muxed_account_to_string(&tx.source_account)
+    );
+    let _ = writeln!(out, "Fee: {} stroops", tx.fee);
+    if let TransactionExt::V1(data) = &tx.ext {
+        let resources = &data.resources;
+        let _ = writeln!(
+            out,
+            "Resources: resource fee {} stroops, {} instructions, {} bytes read, {} bytes written",
+            data.resource_fee, resources.instructions, resources.read_bytes, resources.write_bytes
+        );
+    }
+    let _ = writeln!(out, "Sequence: {}", tx.seq_num.0);
+    let _ = writeln!(out, "Memo: {}", memo::to_string(&tx.memo));
+    let _ = writeln!(out, "Preconditions: {}", preconditions(&tx.cond));
+    let _ = writeln!(out, "Operations: {}", tx.operations.len());
+    for (i, op) in tx.operations.iter().enumerate() {
+        write_operation(&mut out, i, op, specs);
+    }
+    let signatures = utils::envelope_signatures(tx_env);
+    let _ = writeln!(out, "Signatures: {}", signatures.len());
+    for signature in signatures {
+        let _ = writeln!(out, "  hint {}", hex::encode(signature.hint.0));
+    }
+    Ok(out)
+}
+
+/// Transaction of the envelope, the inner one of a fee bump.
+fn transaction(tx_env: &TransactionEnvelope) -> Transaction {
+    match tx_env {
+        TransactionEnvelope::TxV0(env) => utils::transaction_from_v0(&env.tx),
+        TransactionEnvelope::Tx(env) => env.tx.clone(),
+        TransactionEnvelope::TxFeeBump(env) => match &env.tx.inner_tx {
+            FeeBumpTransactionInnerTx::Tx(inner) => inner.tx.clone(),
+        },
+    }
+}
+
+/// Contracts called by the transaction, directly or in its authorizations.
+fn contracts(tx: &Transaction) -> Vec<Hash> {
+    let mut contracts = Vec::new();
+    for op in tx.operations.iter() {
+        if let OperationBody::InvokeHostFunction(body) = &op.body {
+            if let HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: ScAddress::Contract(hash),
+                ..
+            }) = &body.host_function
+            {
+                contracts.push(hash.clone());
+            }
+            contracts.extend(crate::log::auth_contracts(body.auth.iter()));
+        }
+    }
+    contracts.sort();
+    contracts.dedup();
+    contracts
+}
+
+fn write_operation(out: &mut String, i: usize, op: &Operation, specs: &BTreeMap<Hash, Spec>) {
+    let _ = write!(out, "  {i}: {}", op.body.name());
+    if let Some(source) = &op.source_account {
+        let _ = write!(out, " (source {})", utils::muxed_account_to_string(source));
+    }
+    let _ = writeln!(out);
+    let OperationBody::InvokeHostFunction(body) = &op.body else {
+        let json = serde_json::to_string(&op.body).unwrap_or_else(|_| format!("{:?}", op.body));
+        let _ = writeln!(out, "     {json}");
+        return;
+    };
+    match &body.host_function {
+        HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address,
+            function_name,
+            args,
+        }) => {
+            let name = function_name.to_utf8_string_lossy();
+            let _ = writeln!(
+                out,
+                "     Contract: {}",
+                utils::sc_address_to_string(contract_address)
+            );
+            let _ = writeln!(out, "     Function: {name}");
+            let spec = match contract_address {
+                ScAddress::Contract(hash) => specs.get(hash),
+                ScAddress::Account(_) => None,
+            };
+            let inputs = spec.and_then(|spec| spec.find_function(&name).ok());
+            for (i, arg) in args.iter().enumerate() {
+                let input = spec.zip(inputs.and_then(|f| f.inputs.get(i)));
+                let decoded = input.and_then(|(spec, input)| {
+                    let value = spec.xdr_to_json(arg, &input.type_).ok()?;
+                    let type_ = spec
+                        .arg_value_name(&input.type_, 0)
+                        .unwrap_or_else(|| "?".to_string());
+                    Some(format!(
+                        "{}: {type_} = {value}",
+                        input.name.to_utf8_string_lossy()
+                    ))
+                });
+                let arg = decoded.unwrap_or_else(|| {
+                    soroban_spec_tools::to_string(arg).unwrap_or_else(|_| format!("{arg:?}"))
+                });
+                let _ = writeln!(out, "       {arg}");
+            }
+        }
+        HostFunction::CreateContract(CreateContractArgs {
+            contract_id_preimage,
+            executable,
+        }) => {
+            let executable = match executable {
+                ContractExecutable::Wasm(hash) => format!("wasm {hash}"),
+                ContractExecutable::StellarAsset => "Stellar asset".to_string(),
+            };
+            let from = match contract_id_preimage {
+                ContractIdPreimage::Address(preimage) => format!(
+                    "address {} with salt {}",
+                    utils::sc_address_to_string(&preimage.address),
+                    hex::encode(preimage.salt.0)
+                ),
+                ContractIdPreimage::Asset(asset) => format!("asset {asset:?}"),
+            };
+            let _ = writeln!(out, "     Create contract of {executable} from {from}");
+        }
+        HostFunction::UploadContractWasm(wasm) => {
+            let hash = utils::contract_hash(wasm)
+                .map_or_else(|_| "?".to_string(), |hash| hash.to_string());
+            let _ = writeln!(
+                out,
+                "     Upload contract wasm {hash} ({} bytes)",
+                wasm.len()
+            );
+        }
+    }
+    if !body.auth.is_empty() {
+        let _ = writeln!(out, "     Authorizations:");
+        for line in crate::log::auth_tree(body.auth.iter(), specs).lines() {
+            let _ = writeln!(out, "       {line}");
+        }
+    }
+}
+
+fn preconditions(cond: &Preconditions) -> String {
+    match cond {
+        Preconditions::None => "none".to_string(),
+        Preconditions::Time(time_bounds) => time(time_bounds),
+        Preconditions::V2(v2) => {
+            let mut conditions = Vec::new();
+            if let Some(time_bounds) = &v2.time_bounds {
+                conditions.push(time(time_bounds));
+            }
+            if let Some(bounds) = &v2.ledger_bounds {
+                conditions.push(format!(
+                    "ledgers {}..{}",
+                    bounds.min_ledger,
+                    bound(u64::from(bounds.max_ledger))
+                ));
+            }
+            if let Some(min_seq_num) = &v2.min_seq_num {
+                conditions.push(format!("min sequence {}", min_seq_num.0));
+            }
+            if v2.min_seq_age.0 != 0 {
+                conditions.push(format!("min sequence age {}s", v2.min_seq_age.0));
+            }
+            if v2.min_seq_ledger_gap != 0 {
+                conditions.push(format!("min sequence ledger gap {}", v2.min_seq_ledger_gap));
+            }
+            for signer in v2.extra_signers.iter() {
+                let signer =
+                    serde_json::to_string(signer).unwrap_or_else(|_| format!("{signer:?}"));
+                conditions.push(format!("extra signer {signer}"));
+            }
+            if conditions.is_empty() {
+                "none".to_string()
+            } else {
+                conditions.join(", ")
+            }
+        }
+    }
+}
+
+fn time(time_bounds: &TimeBounds) -> String {
+    format!(
+        "time {}..{}",
+        time_bounds.min_time.0,
+        bound(time_bounds.max_time.0)
+    )
+}
+
+/// Upper bound, where 0 means unbounded.
+fn bound(max: u64) -> String {
+    if max == 0 {
+        String::new()
+    } else {
+        max.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_env_host::xdr::{
+        InvokeHostFunctionOp, Memo, MuxedAccount, ScSpecEntry, ScSpecFunctionInputV0,
+        ScSpecFunctionV0, ScSpecTypeDef, ScVal, SequenceNumber, TimePoint, TransactionV1Envelope,
+        Uint256, VecM,
+    };
+
+    #[test]
+    fn test_explain() {
+        let contract = Hash([1; 32]);
+        let tx_env = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: Transaction {
+                source_account: MuxedAccount::Ed25519(Uint256([9; 32])),
+                fee: 100,
+                seq_num: SequenceNumber(7),
+                cond: Preconditions::Time(TimeBounds {
+                    min_time: TimePoint(0),
+                    max_time: TimePoint(1000),
+                }),
+                memo: Memo::Id(42),
+                operations: vec![Operation {
+                    source_account: None,
+                    body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                        host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                            contract_address: ScAddress::Contract(contract.clone()),
+                            function_name: "bump".try_into().unwrap(),
+                            args: vec![ScVal::U32(5), ScVal::U32(6)].try_into().unwrap(),
+                        }),
+                        auth: VecM::default(),
+                    }),
+                }]
+                .try_into()
+                .unwrap(),
+                ext: TransactionExt::V0,
+            },
+            signatures: VecM::default(),
+        });
+        let spec = Spec::new(vec![ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+            doc: "".try_into().unwrap(),
+            name: "bump".try_into().unwrap(),
+            inputs: vec![ScSpecFunctionInputV0 {
+                doc: "".try_into().unwrap(),
+                name: "by".try_into().unwrap(),
+                type_: ScSpecTypeDef::U32,
+            }]
+            .try_into()
+            .unwrap(),
+            outputs: VecM::default(),
+        })]);
+        let passphrase = "Test SDF Network ; September 2015";
+        let TransactionEnvelope::Tx(env) = &tx_env else {
+            unreachable!()
+        };
+        let hash = hex::encode(utils::transaction_hash(&env.tx, passphrase).unwrap());
+
+        assert_eq!(contracts(&transaction(&tx_env)), [contract.clone()]);
+        let explanation = explain(
+            &tx_env,
+            passphrase,
+            &BTreeMap::from([(contract.clone(), spec)]),
+        )
+        .unwrap();
+        assert_eq!(
+            explanation,
+            format!(
+                "Hash: {hash}\n\
+                 Source: {}\n\
+                 Fee: 100 stroops\n\
+                 Sequence: 7\n\
+                 Memo: id 42\n\
+                 Preconditions: time 0..1000\n\
+                 Operations: 1\n  \
+                 0: InvokeHostFunction\n     \
+                 Contract: {}\n     \
+                 Function: bump\n       \
+                 by: u32 = 5\n       \
+                 6\n\
+                 Signatures: 0\n",
+                stellar_strkey::ed25519::PublicKey([9; 32]),
+                stellar_strkey::Contract(contract.0),
+            )
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use clap::{command, Parser};
+use soroban_env_host::xdr::{self, Limits, MuxedAccount, TransactionEnvelope, Uint256, WriteXdr};
+
+use crate::{
+    commands::{
+        config::{self, locator, secret},
+        network, tx,
+    },
+    signer, utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Secret(#[from] secret::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+    #[error("transaction is already a fee bump")]
+    AlreadyFeeBump,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    /// Account paying the fee, which signs the fee bump. Can be an identity (--fee-source alice), a secret key (--fee-source SC36…), or a seed phrase (--fee-source "kite urban…")
+    #[arg(long)]
+    pub fee_source: String,
+    /// Fee to bid for each operation, in stroops, on top of the resource fee of the inner transaction. Raised if needed to at least match the inner transaction's bid
+    #[arg(long, default_value = "100", env = "STELLAR_FEE")]
+    pub fee: u32,
+    /// If using a seed phrase, which hierarchical deterministic path to use, e.g. `m/44'/148'/{hd_path}`. Example: `--hd-path 1`. Default: `0`
+    #[arg(long)]
+    pub hd_path: Option<usize>,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        println!("{}", self.fee_bump()?.to_xdr_base64(Limits::none())?);
+        Ok(())
+    }
+
+    /// Wrap the envelope in a fee bump signed by the fee source.
+    pub fn fee_bump(&self) -> Result<TransactionEnvelope, Error> {
+        let tx_env = self.tx_xdr.envelope()?;
+        if let TransactionEnvelope::TxFeeBump(_) = tx_env {
+            return Err(Error::AlreadyFeeBump);
+        }
+        let config = config::Args {
+            network: self.network.clone(),
+            source_account: self.fee_source.clone(),
+            hd_path: self.hd_path,
+            locator: self.locator.clone(),
+        };
+        let signer = config.signer()?;
+        let network = config.get_network()?;
+        let fee_bump = utils::fee_bump_transaction(
+            &tx_env,
+            MuxedAccount::Ed25519(Uint256(signer.public_key().0)),
+            self.fee,
+        )?;
+        Ok(signer.sign_transaction_envelope(&fee_bump, &network.network_passphrase)?)
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use clap::{command, Parser};
+use soroban_env_host::xdr::{
+    self, Limits, ScVal, SorobanAuthorizationEntry, SorobanCredentials, TransactionEnvelope,
+    TransactionV1Envelope, VecM, WriteXdr,
+};
+
+use crate::{
+    commands::{
+        config::{self, data, locator},
+        global, network, tx, NetworkRunnable,
+    },
+    fee,
+    rpc::{self, Client},
+    utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error(transparent)]
+    Auth(#[from] tx::auth::Error),
+    #[error(transparent)]
+    Fee(#[from] fee::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error("only v1 transaction envelopes carry authorization entries")]
+    UnsupportedEnvelope,
+    #[error("{0} signed authorization entries match none of the transaction's, they may be for another simulation of it")]
+    UnmatchedEntries(usize),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    /// File of signed authorization entries, as written by `tx sign-auth`. Can be given several times
+    #[arg(long = "auth", required = true)]
+    pub auth_files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        let tx_env = self.run_against_rpc_server(Some(global_args), None).await?;
+        println!("{}", tx_env.to_xdr_base64(Limits::none())?);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NetworkRunnable for Cmd {
+    type Error = Error;
+    type Result = TransactionEnvelope;
+
+    /// Replace the transaction's authorization entries with the signed ones, and simulate it
+    /// again as verifying the signatures changes the resources it needs. Any signatures of the
+    /// envelope are dropped, as they no longer match the transaction.
+    async fn run_against_rpc_server(
+        &self,
+        global_args: Option<&global::Args>,
+        config: Option<&config::Args>,
+    ) -> Result<TransactionEnvelope, Error> {
+        let network = if let Some(config) = config {
+            config.get_network()?
+        } else {
+            self.network.get(&self.locator)?
+        };
+        let TransactionEnvelope::Tx(TransactionV1Envelope { mut tx, .. }) =
+            self.tx_xdr.envelope()?
+        else {
+            return Err(Error::UnsupportedEnvelope);
+        };
+        let mut signed = Vec::new();
+        for file in &self.auth_files {
+            // Files signed by one party still hold the unsigned entries of the others
+            signed.extend(
+                tx::auth::read_entries(Some(file))?
+                    .into_iter()
+                    .filter(is_signed),
+            );
+        }
+        let unmatched = utils::replace_auth_entries(&mut tx, &signed)?;
+        if !unmatched.is_empty() {
+            return Err(Error::UnmatchedEntries(unmatched.len()));
+        }
+        for entry in utils::address_auth_entries(&tx) {
+            if let SorobanCredentials::Address(credentials) = &entry.credentials {
+                if !is_signed(&entry) {
+                    tracing::warn!(
+                        "the authorization entry of {} is not signed yet",
+                        utils::sc_address_to_string(&credentials.address)
+                    );
+                }
+            }
+        }
+        let client = Client::new(&network.rpc_url)?;
+        let txn = client.create_assembled_transaction(&tx).await?;
+        if global_args.map_or(true, |a| !a.no_cache) {
+            data::write(txn.sim_response().clone().into(), &network.rpc_uri()?)?;
+        }
+        // Padded by the defaults, as `tx send` takes the transaction as it is
+        let tx = fee::Args::default().apply_to_simulated_txn(txn.transaction().clone())?;
+        Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: VecM::default(),
+        }))
+    }
+}
+
+fn is_signed(entry: &SorobanAuthorizationEntry) -> bool {
+    matches!(&entry.credentials, SorobanCredentials::Address(credentials) if credentials.signature != ScVal::Void)
+}
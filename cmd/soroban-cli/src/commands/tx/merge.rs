@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use soroban_env_host::xdr::{self, Limits, TransactionEnvelope, WriteXdr};
+
+use crate::{commands::tx, utils};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error("{0:?} contains a different transaction than {1:?}")]
+    DifferentTransaction(PathBuf, PathBuf),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Files containing base64 encoded, partially signed copies of the same `TransactionEnvelope`. `-` reads one of them from stdin
+    #[arg(required = true, num_args = 2..)]
+    pub tx_xdr: Vec<PathBuf>,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        println!("{}", self.merge()?.to_xdr_base64(Limits::none())?);
+        Ok(())
+    }
+
+    pub fn merge(&self) -> Result<TransactionEnvelope, Error> {
+        let envelopes = self
+            .tx_xdr
+            .iter()
+            .map(|path| tx::xdr::from_base64(&tx::xdr::read(Some(path))?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (first, rest) = envelopes.split_first().expect("at least two envelopes");
+        let unsigned = utils::strip_signatures(first);
+        rest.iter()
+            .zip(&self.tx_xdr[1..])
+            .try_fold(first.clone(), |merged, (tx_env, path)| {
+                if utils::strip_signatures(tx_env) != unsigned {
+                    return Err(Error::DifferentTransaction(
+                        path.clone(),
+                        self.tx_xdr[0].clone(),
+                    ));
+                }
+                Ok(utils::add_signatures(
+                    &merged,
+                    utils::envelope_signatures(tx_env),
+                )?)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::Signer;
+    use soroban_env_host::xdr::{
+        Memo, MuxedAccount, Preconditions, SequenceNumber, Transaction, TransactionExt, Uint256,
+        VecM,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    fn write_signed(
+        dir: &tempfile::TempDir,
+        name: &str,
+        key: &ed25519_dalek::SigningKey,
+        seq_num: i64,
+    ) -> PathBuf {
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([9; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(seq_num),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        };
+        let tx_env = Signer::Local(key.clone())
+            .sign_transaction(&tx, PASSPHRASE)
+            .unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, tx_env.to_xdr_base64(Limits::none()).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let alice = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let bob = ed25519_dalek::SigningKey::from_bytes(&[2; 32]);
+        let cmd = Cmd {
+            tx_xdr: vec![
+                write_signed(&dir, "alice", &alice, 1),
+                write_signed(&dir, "bob", &bob, 1),
+                write_signed(&dir, "alice-again", &alice, 1),
+            ],
+        };
+        let merged = cmd.merge().unwrap();
+        assert_eq!(utils::envelope_signatures(&merged).len(), 2);
+
+        let cmd = Cmd {
+            tx_xdr: vec![
+                write_signed(&dir, "alice", &alice, 1),
+                write_signed(&dir, "bob-next", &bob, 2),
+            ],
+        };
+        assert!(matches!(
+            cmd.merge(),
+            Err(Error::DifferentTransaction(path, _)) if path.ends_with("bob-next")
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use clap::Parser;
+
+use super::global;
+
+pub mod auth;
+pub mod check_threshold;
+pub mod explain;
+pub mod fee_bump;
+pub mod import_auth;
+pub mod merge;
+pub mod new;
+pub mod send;
+pub mod sign;
+pub mod sign_auth;
+pub mod simulate;
+pub mod xdr;
+
+#[derive(Debug, Parser)]
+#[allow(clippy::large_enum_variant)]
+pub enum Cmd {
+    /// Sign a transaction envelope, appending the signature to any existing ones
+    Sign(sign::Cmd),
+    /// Submit a signed transaction envelope to the network and wait for its result
+    Send(send::Cmd),
+    /// Simulate a transaction envelope and print a report of the resources, footprint and
+    /// authorizations it requires
+    Simulate(simulate::Cmd),
+    /// Merge the signatures of several partially signed copies of the same transaction envelope
+    Merge(merge::Cmd),
+    /// Check whether the signatures of a transaction envelope meet the source account's threshold
+    CheckThreshold(check_threshold::Cmd),
+    /// Print a readable summary of a transaction envelope, with the arguments of contract calls
+    /// named and typed from the contracts' specs
+    Explain(explain::Cmd),
+    /// Wrap a signed transaction envelope in a fee bump paid by another account
+    FeeBump(fee_bump::Cmd),
+    /// Sign the authorization entries exported by `contract invoke --export-auth` that are for the
+    /// source account, passing the others through for their signers
+    SignAuth(sign_auth::Cmd),
+    /// Replace the authorization entries of a transaction envelope with signed ones and simulate it
+    /// again, ready to be signed and sent
+    ImportAuth(import_auth::Cmd),
+    /// Build a transaction of a classic operation, such as a payment, and sign and send it
+    #[command(subcommand)]
+    New(new::Cmd),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sign(#[from] sign::Error),
+    #[error(transparent)]
+    Send(#[from] send::Error),
+    #[error(transparent)]
+    Simulate(#[from] simulate::Error),
+    #[error(transparent)]
+    Merge(#[from] merge::Error),
+    #[error(transparent)]
+    CheckThreshold(#[from] check_threshold::Error),
+    #[error(transparent)]
+    Explain(#[from] explain::Error),
+    #[error(transparent)]
+    FeeBump(#[from] fee_bump::Error),
+    #[error(transparent)]
+    SignAuth(#[from] sign_auth::Error),
+    #[error(transparent)]
+    ImportAuth(#[from] import_auth::Error),
+    #[error(transparent)]
+    New(#[from] new::Error),
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        match self {
+            Cmd::Sign(cmd) => cmd.run()?,
+            Cmd::Send(cmd) => cmd.run(global_args).await?,
+            Cmd::Simulate(cmd) => cmd.run(global_args).await?,
+            Cmd::Merge(cmd) => cmd.run()?,
+            Cmd::CheckThreshold(cmd) => cmd.run().await?,
+            Cmd::Explain(cmd) => cmd.run(global_args).await?,
+            Cmd::FeeBump(cmd) => cmd.run()?,
+            Cmd::SignAuth(cmd) => cmd.run().await?,
+            Cmd::ImportAuth(cmd) => cmd.run(global_args).await?,
+            Cmd::New(cmd) => cmd.run(global_args).await?,
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use clap::{command, Parser};
+use soroban_env_host::xdr::{Asset, ChangeTrustAsset, ChangeTrustOp, OperationBody};
+
+use crate::utils::parsing::parse_asset;
+
+use super::Error;
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Asset of the trustline, `CODE:ISSUER`
+    #[arg(long)]
+    pub line: String,
+    /// Maximum balance of the asset the source account can hold, in stroops. 0 removes the trustline. Default: no limit
+    #[arg(long, default_value_t = i64::MAX)]
+    pub limit: i64,
+    #[command(flatten)]
+    pub tx: super::Args,
+}
+
+impl Cmd {
+    pub fn op_body(&self) -> Result<OperationBody, Error> {
+        let line = match parse_asset(&self.line)? {
+            Asset::Native => return Err(Error::NativeTrustline),
+            Asset::CreditAlphanum4(asset) => ChangeTrustAsset::CreditAlphanum4(asset),
+            Asset::CreditAlphanum12(asset) => ChangeTrustAsset::CreditAlphanum12(asset),
+        };
+        Ok(OperationBody::ChangeTrust(ChangeTrustOp {
+            line,
+            limit: self.limit,
+        }))
+    }
+}
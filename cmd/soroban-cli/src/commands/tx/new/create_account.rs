@@ -0,0 +1,26 @@
+use clap::{command, Parser};
+use soroban_env_host::xdr::{CreateAccountOp, OperationBody};
+
+use super::Error;
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Account to create. Can be a public key or an identity
+    #[arg(long)]
+    pub destination: String,
+    /// Amount of XLM sent to the new account, in stroops. It must cover the account's minimum balance
+    #[arg(long, default_value = "10000000")]
+    pub starting_balance: i64,
+    #[command(flatten)]
+    pub tx: super::Args,
+}
+
+impl Cmd {
+    pub fn op_body(&self) -> Result<OperationBody, Error> {
+        Ok(OperationBody::CreateAccount(CreateAccountOp {
+            destination: self.tx.account_id(&self.destination)?,
+            starting_balance: self.starting_balance,
+        }))
+    }
+}
@@ -0,0 +1,144 @@
+use clap::Parser;
+use soroban_env_host::xdr::{
+    self, AccountId, Memo, MuxedAccount, Operation, OperationBody, Preconditions, PublicKey,
+    SequenceNumber, Transaction, TransactionExt,
+};
+
+use crate::{
+    commands::{
+        config::{self, data},
+        global, network,
+        txn_result::{TxnEnvelopeResult, TxnResult},
+    },
+    fee,
+    rpc::{self, Client},
+    utils, Pwd,
+};
+
+pub mod change_trust;
+pub mod create_account;
+pub mod payment;
+pub mod set_options;
+
+#[derive(Debug, Parser)]
+pub enum Cmd {
+    /// Send an amount of an asset to an account
+    Payment(payment::Cmd),
+    /// Create and fund a new account
+    CreateAccount(create_account::Cmd),
+    /// Add, change the limit of, or remove a trustline to an asset
+    ChangeTrust(change_trust::Cmd),
+    /// Set the signers, thresholds, flags and home domain of the source account
+    SetOptions(set_options::Cmd),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Fee(#[from] fee::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error(transparent)]
+    Asset(#[from] utils::parsing::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error("classic operations cannot be simulated, use --build-only to output the transaction")]
+    CannotSimulate,
+    #[error("cannot add a trustline to the native asset")]
+    NativeTrustline,
+    #[error("home domain {0:?} is longer than 32 bytes")]
+    HomeDomainTooLong(String),
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        let (args, body) = match self {
+            Cmd::Payment(cmd) => (&cmd.tx, cmd.op_body()?),
+            Cmd::CreateAccount(cmd) => (&cmd.tx, cmd.op_body()?),
+            Cmd::ChangeTrust(cmd) => (&cmd.tx, cmd.op_body()?),
+            Cmd::SetOptions(cmd) => (&cmd.tx, cmd.op_body()?),
+        };
+        match args.send(body, Some(global_args)).await?.to_envelope() {
+            TxnEnvelopeResult::TxnEnvelope(tx) => args.fee.print_envelope(&tx, &args.config)?,
+            TxnEnvelopeResult::Res(hash) => println!("{}", hex::encode(hash)),
+        }
+        Ok(())
+    }
+}
+
+/// Options shared by the operations: the source account and network, and the transaction options.
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Args {
+    #[command(flatten)]
+    pub config: config::Args,
+    #[command(flatten)]
+    pub fee: fee::Args,
+}
+
+impl Pwd for Args {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.config.set_pwd(pwd);
+    }
+}
+
+impl Args {
+    /// Build a transaction of the operation from the source account, and sign and send it unless
+    /// `--build-only` is given. The result is the hash of the sent transaction.
+    pub async fn send(
+        &self,
+        body: OperationBody,
+        global_args: Option<&global::Args>,
+    ) -> Result<TxnResult<[u8; 32]>, Error> {
+        if self.fee.sim_only {
+            return Err(Error::CannotSimulate);
+        }
+        let network = self.config.get_network()?;
+        let client = Client::new(&network.rpc_url)?;
+        if !self.fee.build_only {
+            client
+                .verify_network_passphrase(Some(&network.network_passphrase))
+                .await?;
+        }
+        let sequence = self.fee.next_sequence(&client, &self.config).await?;
+        let tx = self.fee.apply_to_txn(Transaction {
+            source_account: self.config.source_muxed_account()?,
+            fee: self.fee.fee,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body,
+            }]
+            .try_into()?,
+            ext: TransactionExt::V0,
+        })?;
+        if self.fee.build_only {
+            return Ok(TxnResult::Txn(tx));
+        }
+        let (hash, res) = self.fee.send_transaction(&client, tx, &self.config).await?;
+        if global_args.map_or(true, |a| !a.no_cache) {
+            data::write(res.try_into()?, &network.rpc_uri()?)?;
+        }
+        Ok(TxnResult::Res(hash))
+    }
+
+    /// Account of `address`, a public key, muxed address or identity.
+    pub fn muxed_account(&self, address: &str) -> Result<MuxedAccount, Error> {
+        Ok(self.config.muxed_account(address)?)
+    }
+
+    /// Like `muxed_account`, without the mux id.
+    pub fn account_id(&self, address: &str) -> Result<AccountId, Error> {
+        Ok(AccountId(PublicKey::PublicKeyTypeEd25519(
+            utils::muxed_account_ed25519(&self.muxed_account(address)?),
+        )))
+    }
+}
@@ -0,0 +1,32 @@
+use clap::{command, Parser};
+use soroban_env_host::xdr::{OperationBody, PaymentOp};
+
+use crate::utils::parsing::parse_asset;
+
+use super::Error;
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Account receiving the payment. Can be a public key, a muxed address or an identity
+    #[arg(long)]
+    pub destination: String,
+    /// Asset to send, `native` or `CODE:ISSUER`
+    #[arg(long, default_value = "native")]
+    pub asset: String,
+    /// Amount to send, in stroops of the asset. 1 stroop = 0.0000001 of a unit
+    #[arg(long)]
+    pub amount: i64,
+    #[command(flatten)]
+    pub tx: super::Args,
+}
+
+impl Cmd {
+    pub fn op_body(&self) -> Result<OperationBody, Error> {
+        Ok(OperationBody::Payment(PaymentOp {
+            destination: self.tx.muxed_account(&self.destination)?,
+            asset: parse_asset(&self.asset)?,
+            amount: self.amount,
+        }))
+    }
+}
@@ -0,0 +1,126 @@
+use clap::{command, Parser, ValueEnum};
+use soroban_env_host::xdr::{
+    AccountFlags, OperationBody, SetOptionsOp, Signer, SignerKey, StringM,
+};
+
+use crate::utils;
+
+use super::Error;
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// Weight of the source account's own key. 0 disables it
+    #[arg(long)]
+    pub master_weight: Option<u8>,
+    /// Signature weight needed by low threshold operations, such as bumping the sequence number
+    #[arg(long)]
+    pub low_threshold: Option<u8>,
+    /// Signature weight needed by medium threshold operations, such as payments
+    #[arg(long)]
+    pub med_threshold: Option<u8>,
+    /// Signature weight needed by high threshold operations, such as changing the signers
+    #[arg(long)]
+    pub high_threshold: Option<u8>,
+    /// Home domain of the account, for looking up its stellar.toml
+    #[arg(long)]
+    pub home_domain: Option<String>,
+    /// Add, update or remove a signer of the account. Can be a public key or an identity
+    #[arg(long, requires = "signer_weight")]
+    pub signer: Option<String>,
+    /// Weight of --signer. 0 removes it
+    #[arg(long, requires = "signer")]
+    pub signer_weight: Option<u8>,
+    /// Account flag to set. Can be given several times
+    #[arg(long, value_enum)]
+    pub set_flag: Vec<Flag>,
+    /// Account flag to clear. Can be given several times
+    #[arg(long, value_enum)]
+    pub clear_flag: Vec<Flag>,
+    #[command(flatten)]
+    pub tx: super::Args,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Flag {
+    /// Trustlines to the account's assets need its authorization
+    AuthRequired,
+    /// The account can revoke the authorization of trustlines to its assets
+    AuthRevocable,
+    /// The account's flags and signers can no longer change, nor can it be merged
+    AuthImmutable,
+    /// The account can claw back its assets
+    AuthClawbackEnabled,
+}
+
+impl From<Flag> for AccountFlags {
+    fn from(flag: Flag) -> Self {
+        match flag {
+            Flag::AuthRequired => AccountFlags::RequiredFlag,
+            Flag::AuthRevocable => AccountFlags::RevocableFlag,
+            Flag::AuthImmutable => AccountFlags::ImmutableFlag,
+            Flag::AuthClawbackEnabled => AccountFlags::ClawbackEnabledFlag,
+        }
+    }
+}
+
+impl Cmd {
+    pub fn op_body(&self) -> Result<OperationBody, Error> {
+        let signer = match (&self.signer, self.signer_weight) {
+            (Some(signer), Some(weight)) => {
+                let account = self.tx.muxed_account(signer)?;
+                Some(Signer {
+                    key: SignerKey::Ed25519(utils::muxed_account_ed25519(&account)),
+                    weight: weight.into(),
+                })
+            }
+            _ => None,
+        };
+        let home_domain = self
+            .home_domain
+            .as_ref()
+            .map(|domain| {
+                StringM::<32>::try_from(domain.as_str())
+                    .map(Into::into)
+                    .map_err(|_| Error::HomeDomainTooLong(domain.clone()))
+            })
+            .transpose()?;
+        Ok(OperationBody::SetOptions(SetOptionsOp {
+            inflation_dest: None,
+            clear_flags: flags(&self.clear_flag),
+            set_flags: flags(&self.set_flag),
+            master_weight: self.master_weight.map(Into::into),
+            low_threshold: self.low_threshold.map(Into::into),
+            med_threshold: self.med_threshold.map(Into::into),
+            high_threshold: self.high_threshold.map(Into::into),
+            home_domain,
+            signer,
+        }))
+    }
+}
+
+fn flags(flags: &[Flag]) -> Option<u32> {
+    if flags.is_empty() {
+        None
+    } else {
+        Some(
+            flags
+                .iter()
+                .fold(0, |all, flag| all | AccountFlags::from(*flag) as u32),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(flags(&[]), None);
+        assert_eq!(
+            flags(&[Flag::AuthRequired, Flag::AuthClawbackEnabled]),
+            Some(9)
+        );
+    }
+}
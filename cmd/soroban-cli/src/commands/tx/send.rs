@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use clap::{command, Parser};
+use soroban_env_host::xdr::{
+    self, FeeBumpTransactionInnerTx, HostFunction, InvokeContractArgs, Limits, OperationBody,
+    ScAddress, TransactionEnvelope, TransactionResult, WriteXdr,
+};
+use soroban_spec_tools::Spec;
+
+use crate::{
+    commands::{
+        config::{self, data, locator},
+        contract::invoke,
+        global, network, tx,
+        txn_result::TxnResult,
+        NetworkRunnable,
+    },
+    rpc::{self, Client, GetTransactionResponse},
+    utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Invoke(#[from] invoke::Error),
+    #[error(transparent)]
+    Spec(#[from] soroban_spec_tools::Error),
+    #[error("transaction {hash} failed with result {result}")]
+    TransactionFailed { hash: String, result: String },
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        let res = self.run_against_rpc_server(Some(global_args), None).await?;
+        if let Some(TransactionResult { result, .. }) = &res.result {
+            eprintln!("Transaction result: {}", result.name());
+        }
+        if let Ok(events) = res.contract_events() {
+            crate::log::diagnostic_events(&events, tracing::Level::INFO);
+        }
+        if res.return_value().is_ok() {
+            let client = Client::new(&self.network.get(&self.locator)?.rpc_url)?;
+            println!("{}", output(&client, &res, Some(global_args)).await?);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NetworkRunnable for Cmd {
+    type Error = Error;
+    type Result = GetTransactionResponse;
+
+    async fn run_against_rpc_server(
+        &self,
+        global_args: Option<&global::Args>,
+        config: Option<&config::Args>,
+    ) -> Result<GetTransactionResponse, Error> {
+        let network = if let Some(config) = config {
+            config.get_network()?
+        } else {
+            self.network.get(&self.locator)?
+        };
+        let tx_env = self.tx_xdr.envelope()?;
+        let client = Client::new(&network.rpc_url)?;
+        client
+            .verify_network_passphrase(Some(&network.network_passphrase))
+            .await?;
+        let hash = hex::encode(utils::transaction_envelope_hash(
+            &tx_env,
+            &network.network_passphrase,
+        )?);
+        tracing::info!("Sending transaction {hash}");
+        tracing::trace!("{}", tx_env.to_xdr_base64(Limits::none())?);
+        let res = match client.send_transaction(&tx_env).await {
+            Err(rpc::Error::TransactionSubmissionFailed(error)) => {
+                return Err(failure(&client, hash, error).await)
+            }
+            res => res?,
+        };
+        if global_args.map_or(true, |a| !a.no_cache) {
+            data::write(res.clone().try_into()?, &network.rpc_uri()?)?;
+        }
+        Ok(res)
+    }
+}
+
+/// Error of a transaction the network rejected. One included in a ledger is fetched again, for
+/// its result code and events.
+async fn failure(client: &Client, hash: String, error: String) -> Error {
+    let res: Option<GetTransactionResponse> = match client.get_transaction(&hash).await {
+        Ok(raw) => raw.try_into().ok(),
+        Err(_) => None,
+    };
+    let Some(res) = res.filter(|res| res.status == "FAILED") else {
+        return Error::Rpc(rpc::Error::TransactionSubmissionFailed(error));
+    };
+    if let Ok(events) = res.events() {
+        crate::log::diagnostic_events(&events, tracing::Level::ERROR);
+    }
+    match res.result {
+        Some(TransactionResult { result, .. }) => Error::TransactionFailed {
+            hash,
+            result: result.name().to_string(),
+        },
+        None => Error::Rpc(rpc::Error::TransactionSubmissionFailed(error)),
+    }
+}
+
+/// Return value of the transaction, printed with the spec of the contract it invokes, as
+/// `contract invoke` does.
+async fn output(
+    client: &Client,
+    res: &GetTransactionResponse,
+    global_args: Option<&global::Args>,
+) -> Result<String, Error> {
+    let return_value = res.return_value()?;
+    if let Some(InvokeContractArgs {
+        contract_address: ScAddress::Contract(contract_id),
+        function_name,
+        ..
+    }) = res.envelope.as_ref().and_then(invoked_contract)
+    {
+        let spec_entries = invoke::fetch_spec_entries(client, &contract_id.0, global_args).await?;
+        if let TxnResult::Res(output) = invoke::output_to_string(
+            &Spec::new(spec_entries),
+            &return_value,
+            &function_name.to_utf8_string_lossy(),
+        )? {
+            return Ok(output);
+        }
+    }
+    Ok(soroban_spec_tools::to_json(&return_value)?.to_string())
+}
+
+/// Contract function invoked by the transaction, if it invokes one.
+fn invoked_contract(tx_env: &TransactionEnvelope) -> Option<InvokeContractArgs> {
+    let operations = match tx_env {
+        TransactionEnvelope::TxV0(env) => &env.tx.operations,
+        TransactionEnvelope::Tx(env) => &env.tx.operations,
+        TransactionEnvelope::TxFeeBump(env) => {
+            let FeeBumpTransactionInnerTx::Tx(inner) = &env.tx.inner_tx;
+            &inner.tx.operations
+        }
+    };
+    match &operations.first()?.body {
+        OperationBody::InvokeHostFunction(op) => match &op.host_function {
+            HostFunction::InvokeContract(args) => Some(args.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
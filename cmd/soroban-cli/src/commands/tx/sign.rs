@@ -0,0 +1,48 @@
+use clap::{command, Parser};
+use soroban_env_host::xdr::{self, Limits, TransactionEnvelope, WriteXdr};
+
+use crate::{
+    commands::{config, tx},
+    signer, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    #[command(flatten)]
+    pub config: config::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.config.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Error> {
+        println!("{}", self.sign()?.to_xdr_base64(Limits::none())?);
+        Ok(())
+    }
+
+    pub fn sign(&self) -> Result<TransactionEnvelope, Error> {
+        let tx_env = self.tx_xdr.envelope()?;
+        let signer = self.config.signer()?;
+        let network = self.config.get_network()?;
+        Ok(signer.sign_transaction_envelope(&tx_env, &network.network_passphrase)?)
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use clap::{command, Parser};
+use soroban_env_host::xdr::{self, SorobanAuthorizationEntry};
+
+use crate::{
+    commands::{config, network, tx},
+    rpc::{self, Client},
+    signer, utils, Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Auth(#[from] tx::auth::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+    #[error("none of the authorization entries are for {0}")]
+    NoEntriesForKey(stellar_strkey::ed25519::PublicKey),
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    /// File of authorization entries, as written by `contract invoke --export-auth`. If omitted or `-`, they are read from stdin
+    pub auth_file: Option<PathBuf>,
+    /// Ledger sequence after which the signatures are no longer valid. Defaults to 60 ledgers, about 5 minutes, after the latest ledger
+    #[arg(long)]
+    pub signature_expiration_ledger: Option<u32>,
+    #[command(flatten)]
+    pub config: config::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.config.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self) -> Result<(), Error> {
+        println!("{}", tx::auth::to_lines(&self.sign().await?)?);
+        Ok(())
+    }
+
+    /// Sign the entries authorized by the source account, leaving the others as they are so that
+    /// the output can be passed on to the next signer.
+    pub async fn sign(&self) -> Result<Vec<SorobanAuthorizationEntry>, Error> {
+        let entries = tx::auth::read_entries(self.auth_file.as_deref())?;
+        let key = self.config.signer()?;
+        let public_key = key.public_key();
+        let network = self.config.get_network()?;
+        let signature_expiration_ledger = if let Some(ledger) = self.signature_expiration_ledger {
+            ledger
+        } else {
+            let client = Client::new(&network.rpc_url)?;
+            client.get_latest_ledger().await?.sequence + 60
+        };
+        let mut signed = 0;
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                if utils::auth_entry_account(entry).map(|k| k.0) == Some(public_key.0) {
+                    signed += 1;
+                    key.sign_auth_entry(
+                        entry,
+                        signature_expiration_ledger,
+                        &network.network_passphrase,
+                    )
+                } else {
+                    Ok(entry.clone())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if signed == 0 {
+            return Err(Error::NoEntriesForKey(public_key));
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use clap::{command, Parser};
+use serde::Serialize;
+use soroban_env_host::xdr::{
+    self, DiagnosticEvent, LedgerKey, ReadXdr, ScVal, SorobanAuthorizationEntry, SorobanResources,
+    SorobanTransactionData,
+};
+
+use crate::{
+    commands::{
+        config::{self, data, locator},
+        global, network, tx, NetworkRunnable,
+    },
+    rpc::{self, Client, SimulateTransactionResponse},
+    Pwd,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Network(#[from] network::Error),
+    #[error(transparent)]
+    Rpc(#[from] rpc::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error(transparent)]
+    TxXdr(#[from] tx::xdr::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("transaction simulation failed: {error}\n{}", event_list(.events))]
+    SimulationFailed {
+        error: String,
+        events: Vec<DiagnosticEvent>,
+    },
+}
+
+/// Events of a failed simulation, one JSON object per line as in the report.
+fn event_list(events: &[DiagnosticEvent]) -> String {
+    let mut list = format!("Events: {}", events.len());
+    for (i, event) in events.iter().enumerate() {
+        let json = serde_json::to_string(event).unwrap_or_else(|_| format!("{event:?}"));
+        list.push_str(&format!("\n  {i}: {json}"));
+    }
+    list
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-oriented report
+    Text,
+    /// JSON report
+    Json,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[group(skip)]
+pub struct Cmd {
+    #[command(flatten)]
+    pub tx_xdr: tx::xdr::Args,
+    /// Format of the simulation report
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+    #[command(flatten)]
+    pub network: network::Args,
+    #[command(flatten)]
+    pub locator: locator::Args,
+}
+
+impl Pwd for Cmd {
+    fn set_pwd(&mut self, pwd: &std::path::Path) {
+        self.locator.set_pwd(pwd);
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self, global_args: &global::Args) -> Result<(), Error> {
+        let sim_res = self.run_against_rpc_server(Some(global_args), None).await?;
+        let report = Report::try_from(&sim_res)?;
+        match self.output {
+            OutputFormat::Text => print!("{report}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NetworkRunnable for Cmd {
+    type Error = Error;
+    type Result = SimulateTransactionResponse;
+
+    async fn run_against_rpc_server(
+        &self,
+        global_args: Option<&global::Args>,
+        config: Option<&config::Args>,
+    ) -> Result<SimulateTransactionResponse, Error> {
+        let network = if let Some(config) = config {
+            config.get_network()?
+        } else {
+            self.network.get(&self.locator)?
+        };
+        let tx_env = self.tx_xdr.envelope()?;
+        let client = Client::new(&network.rpc_url)?;
+        let sim_res = client.simulate_transaction(&tx_env).await?;
+        if global_args.map_or(true, |a| !a.no_cache) {
+            data::write(sim_res.clone().into(), &network.rpc_uri()?)?;
+        }
+        Ok(sim_res)
+    }
+}
+
+/// Structured view of a `simulateTransaction` response.
+#[derive(Serialize, Debug, Clone)]
+pub struct Report {
+    pub min_resource_fee: u64,
+    pub instructions: u32,
+    pub read_bytes: u32,
+    pub write_bytes: u32,
+    pub footprint: Footprint,
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub results: Vec<ScVal>,
+    pub events: Vec<DiagnosticEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restore: Option<Restore>,
+    pub latest_ledger: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Footprint {
+    pub read_only: Vec<LedgerKey>,
+    pub read_write: Vec<LedgerKey>,
+}
+
+/// Archived entries which must be restored before the transaction can succeed.
+#[derive(Serialize, Debug, Clone)]
+pub struct Restore {
+    pub min_resource_fee: u64,
+    pub keys: Vec<LedgerKey>,
+}
+
+impl TryFrom<&SimulateTransactionResponse> for Report {
+    type Error = Error;
+
+    fn try_from(sim_res: &SimulateTransactionResponse) -> Result<Self, Error> {
+        // A failed simulation has no transaction data, only the error and the events leading to it
+        if let Some(error) = &sim_res.error {
+            return Err(Error::SimulationFailed {
+                error: error.clone(),
+                events: sim_res.events()?,
+            });
+        }
+        let SorobanTransactionData {
+            resources:
+                SorobanResources {
+                    footprint,
+                    instructions,
+                    read_bytes,
+                    write_bytes,
+                },
+            ..
+        } = sim_res.transaction_data()?;
+        let results = sim_res.results()?;
+        let restore = sim_res
+            .restore_preamble
+            .as_ref()
+            .map(|preamble| {
+                let data = SorobanTransactionData::from_xdr_base64(
+                    &preamble.transaction_data,
+                    xdr::Limits::none(),
+                )?;
+                Ok::<_, Error>(Restore {
+                    min_resource_fee: preamble.min_resource_fee,
+                    keys: data.resources.footprint.read_write.to_vec(),
+                })
+            })
+            .transpose()?;
+        Ok(Report {
+            min_resource_fee: sim_res.min_resource_fee,
+            instructions,
+            read_bytes,
+            write_bytes,
+            footprint: Footprint {
+                read_only: footprint.read_only.to_vec(),
+                read_write: footprint.read_write.to_vec(),
+            },
+            auth: results.iter().flat_map(|r| r.auth.clone()).collect(),
+            results: results.into_iter().map(|r| r.xdr).collect(),
+            events: sim_res.events()?,
+            restore,
+            latest_ledger: sim_res.latest_ledger,
+        })
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Min resource fee: {}", self.min_resource_fee)?;
+        writeln!(f, "Instructions: {}", self.instructions)?;
+        writeln!(f, "Bytes read: {}", self.read_bytes)?;
+        writeln!(f, "Bytes written: {}", self.write_bytes)?;
+        write_list(f, "Read-only footprint", &self.footprint.read_only)?;
+        write_list(f, "Read-write footprint", &self.footprint.read_write)?;
+        write_list(f, "Authorizations", &self.auth)?;
+        write_list(f, "Results", &self.results)?;
+        write_list(f, "Events", &self.events)?;
+        if let Some(restore) = &self.restore {
+            writeln!(
+                f,
+                "Restore required (min resource fee: {}):",
+                restore.min_resource_fee
+            )?;
+            write_list(f, "Archived entries", &restore.keys)?;
+        }
+        writeln!(f, "Latest ledger: {}", self.latest_ledger)
+    }
+}
+
+fn write_list<T: Serialize>(
+    f: &mut std::fmt::Formatter<'_>,
+    title: &str,
+    items: &[T],
+) -> std::fmt::Result {
+    writeln!(f, "{title}: {}", items.len())?;
+    for (i, item) in items.iter().enumerate() {
+        let json = serde_json::to_string(item).map_err(|_| std::fmt::Error)?;
+        writeln!(f, "  {i}: {json}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_env_host::xdr::{
+        ExtensionPoint, Hash, LedgerFootprint, LedgerKeyContractCode, Limits, VecM, WriteXdr,
+    };
+
+    #[test]
+    fn test_report_from_simulation() {
+        let key = LedgerKey::ContractCode(LedgerKeyContractCode {
+            hash: Hash([1; 32]),
+        });
+        let transaction_data = SorobanTransactionData {
+            ext: ExtensionPoint::V0,
+            resources: SorobanResources {
+                footprint: LedgerFootprint {
+                    read_only: vec![key.clone()].try_into().unwrap(),
+                    read_write: VecM::default(),
+                },
+                instructions: 1000,
+                read_bytes: 20,
+                write_bytes: 0,
+            },
+            resource_fee: 300,
+        };
+        let sim_res = SimulateTransactionResponse {
+            min_resource_fee: 300,
+            transaction_data: transaction_data.to_xdr_base64(Limits::none()).unwrap(),
+            latest_ledger: 42,
+            ..Default::default()
+        };
+        let report = Report::try_from(&sim_res).unwrap();
+        assert_eq!(report.footprint.read_only, vec![key]);
+        assert_eq!(report.instructions, 1000);
+        assert!(report.restore.is_none());
+        assert!(report
+            .to_string()
+            .contains("Read-only footprint: 1\n  0: {\"contract_code\""));
+    }
+
+    #[test]
+    fn test_report_from_failed_simulation() {
+        let event = DiagnosticEvent {
+            in_successful_contract_call: false,
+            event: xdr::ContractEvent {
+                ext: ExtensionPoint::V0,
+                contract_id: None,
+                type_: xdr::ContractEventType::Diagnostic,
+                body: xdr::ContractEventBody::V0(xdr::ContractEventV0 {
+                    topics: vec![ScVal::Symbol("error".try_into().unwrap())]
+                        .try_into()
+                        .unwrap(),
+                    data: ScVal::U32(7),
+                }),
+            },
+        };
+        let sim_res = SimulateTransactionResponse {
+            error: Some("HostError: Error(WasmVm, InvalidAction)".to_string()),
+            events: vec![event.to_xdr_base64(Limits::none()).unwrap()],
+            latest_ledger: 42,
+            ..Default::default()
+        };
+        let err = Report::try_from(&sim_res).unwrap_err();
+        assert!(matches!(&err, Error::SimulationFailed { events, .. } if events == &[event]));
+        let message = err.to_string();
+        assert!(message.contains("HostError: Error(WasmVm, InvalidAction)"));
+        assert!(message.contains("Events: 1\n  0: {"));
+    }
+}
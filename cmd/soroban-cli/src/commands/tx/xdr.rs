@@ -0,0 +1,57 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use soroban_env_host::xdr::{self, Limits, ReadXdr, TransactionEnvelope};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("reading file {0:?}: {1}")]
+    CannotReadFile(PathBuf, io::Error),
+    #[error("reading transaction from stdin: {0}")]
+    CannotReadStdin(io::Error),
+    #[error("decoding transaction envelope: {0}")]
+    Xdr(#[from] xdr::Error),
+}
+
+#[derive(Debug, clap::Args, Clone, Default)]
+#[group(skip)]
+pub struct Args {
+    /// File containing a base64 encoded `TransactionEnvelope`. If omitted or `-`, it is read from stdin
+    pub tx_xdr: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn read(&self) -> Result<String, Error> {
+        read(self.tx_xdr.as_deref())
+    }
+
+    pub fn envelope(&self) -> Result<TransactionEnvelope, Error> {
+        from_base64(&self.read()?)
+    }
+}
+
+/// Read the contents of `path`, or of stdin if it is `None` or `-`.
+pub fn read(path: Option<&Path>) -> Result<String, Error> {
+    match path {
+        Some(path) if path != Path::new("-") => {
+            fs::read_to_string(path).map_err(|e| Error::CannotReadFile(path.to_path_buf(), e))
+        }
+        _ => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(Error::CannotReadStdin)?;
+            Ok(buf)
+        }
+    }
+}
+
+pub fn from_base64(s: &str) -> Result<TransactionEnvelope, Error> {
+    Ok(TransactionEnvelope::from_xdr_base64(
+        s.trim(),
+        Limits::none(),
+    )?)
+}
@@ -1,11 +1,49 @@
 use clap::arg;
 
-use soroban_env_host::xdr;
-use soroban_rpc::Assembled;
+use soroban_env_host::xdr::{
+    self, ExtensionPoint, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+    ReadXdr, RestoreFootprintOp, SequenceNumber, SorobanCredentials, SorobanTransactionData,
+    Transaction, TransactionEnvelope, TransactionExt, Uint256, WriteXdr,
+};
+use soroban_rpc::{
+    Assembled, Client, GetTransactionResponse, LogEvents, LogResources, RestorePreamble,
+};
 
-use crate::commands::HEADING_RPC;
+use crate::commands::{config, HEADING_RPC};
+use crate::{memo, preconditions, sep7, sequence, signer, signer::Signer, utils};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Secret(#[from] config::secret::Error),
+    #[error(transparent)]
+    Rpc(#[from] soroban_rpc::Error),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+    #[error(transparent)]
+    Preconditions(#[from] preconditions::Error),
+    #[error(transparent)]
+    Memo(#[from] memo::Error),
+    #[error(transparent)]
+    Sequence(#[from] sequence::Error),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+    #[error(transparent)]
+    Sep7(#[from] sep7::Error),
+    #[error("{0} ledger entries used by the transaction are archived, restore them first with `contract restore` or pass --auto-restore")]
+    ArchivedEntries(usize),
+    #[error("fee of {0} stroops is too large")]
+    LargeFee(u64),
+    #[error("resource fee of {fee} stroops is more than the --max-resource-fee of {max}")]
+    ResourceFeeAboveMax { fee: u64, max: u64 },
+    #[error("total fee of {fee} stroops is more than the --max-total-fee of {max}")]
+    TotalFeeAboveMax { fee: u64, max: u64 },
+}
 
 #[derive(Debug, clap::Args, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 #[group(skip)]
 pub struct Args {
     /// fee amount for transaction, in stroops. 1 stroop = 0.0000001 xlm
@@ -17,35 +55,381 @@ pub struct Args {
     /// Number of instructions to simulate
     #[arg(long, help_heading = HEADING_RPC)]
     pub instructions: Option<u32>,
-    /// Build the transaction only write the base64 xdr to stdout
+    /// Percentage added to the simulated number of instructions, unless --instructions is given
+    #[arg(long, default_value = "0", value_name = "PERCENT", help_heading = HEADING_RPC)]
+    pub instructions_padding: u32,
+    /// Percentage added to the simulated number of bytes read
+    #[arg(long, default_value = "0", value_name = "PERCENT", help_heading = HEADING_RPC)]
+    pub read_bytes_padding: u32,
+    /// Percentage added to the simulated number of bytes written
+    #[arg(long, default_value = "0", value_name = "PERCENT", help_heading = HEADING_RPC)]
+    pub write_bytes_padding: u32,
+    /// Percentage added to the simulated resource fee. The fee pays for the declared resources, so it should be padded at least as much as they are
+    #[arg(long, default_value = "0", value_name = "PERCENT", help_heading = HEADING_RPC)]
+    pub resource_fee_padding: u32,
+    /// Abort before signing if the resource fee of the simulated transaction, in stroops, is more than this
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub max_resource_fee: Option<u64>,
+    /// Abort before signing if the total fee of the simulated transaction, in stroops, is more than this
     #[arg(long, help_heading = HEADING_RPC)]
+    pub max_total_fee: Option<u64>,
+    /// Build the transaction only write the base64 xdr to stdout
+    #[arg(long, help_heading = HEADING_RPC, group = "output_only")]
     pub build_only: bool,
     /// Simulation the transaction only write the base64 xdr to stdout
-    #[arg(long, help_heading = HEADING_RPC, conflicts_with = "build_only")]
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with = "build_only", group = "output_only")]
     pub sim_only: bool,
+    /// Account paying the fee instead of the source account, by wrapping the signed transaction in a fee bump. Can be an identity (--fee-source alice) or a secret key (--fee-source SC36…)
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with_all = ["build_only", "sim_only"])]
+    pub fee_source: Option<String>,
+    /// Restore the archived ledger entries the transaction uses, in a transaction of its own sent before it
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with_all = ["build_only", "sim_only"])]
+    pub auto_restore: bool,
+    #[command(flatten)]
+    pub preconditions: preconditions::Args,
+    #[command(flatten)]
+    pub memo: memo::Args,
+    #[command(flatten)]
+    pub sequence: sequence::Args,
+    #[command(flatten)]
+    pub sep7: sep7::Args,
 }
 
 impl Args {
-    pub fn apply_to_assembled_txn(&self, txn: Assembled) -> Assembled {
-        if let Some(instructions) = self.instructions {
-            txn.set_max_instructions(instructions)
+    /// Set the transaction options, such as its preconditions and memo, on a newly built
+    /// transaction.
+    pub fn apply_to_txn(&self, mut tx: Transaction) -> Result<Transaction, Error> {
+        tx.cond = self.preconditions.preconditions()?;
+        tx.memo = self.memo.memo()?;
+        if (self.build_only || self.sim_only) && tx.memo != xdr::Memo::None {
+            tracing::info!("Memo: {}", memo::to_string(&tx.memo));
+        }
+        Ok(tx)
+    }
+
+    /// Pad the resources and resource fee of a simulated transaction, raising its fee to match,
+    /// and check the fee against `--max-resource-fee` and `--max-total-fee`.
+    pub fn apply_to_simulated_txn(&self, mut tx: Transaction) -> Result<Transaction, Error> {
+        if let TransactionExt::V1(SorobanTransactionData {
+            resources,
+            resource_fee,
+            ..
+        }) = &mut tx.ext
+        {
+            resources.instructions = self
+                .instructions
+                .unwrap_or_else(|| pad_u32(resources.instructions, self.instructions_padding));
+            resources.read_bytes = pad_u32(resources.read_bytes, self.read_bytes_padding);
+            resources.write_bytes = pad_u32(resources.write_bytes, self.write_bytes_padding);
+            let simulated = u64::try_from(*resource_fee).unwrap_or_default();
+            let padded = pad(simulated, self.resource_fee_padding);
+            tx.fee = u32::try_from(u64::from(tx.fee).saturating_add(padded - simulated))
+                .map_err(|_| Error::LargeFee(padded))?;
+            *resource_fee = i64::try_from(padded).map_err(|_| Error::LargeFee(padded))?;
+        }
+        self.check_max_fees(&tx)?;
+        Ok(tx)
+    }
+
+    fn check_max_fees(&self, tx: &Transaction) -> Result<(), Error> {
+        if let (Some(max), TransactionExt::V1(data)) = (self.max_resource_fee, &tx.ext) {
+            let fee = u64::try_from(data.resource_fee).unwrap_or_default();
+            if fee > max {
+                return Err(Error::ResourceFeeAboveMax { fee, max });
+            }
+        }
+        if let Some(max) = self.max_total_fee {
+            let fee = u64::from(tx.fee);
+            if fee > max {
+                return Err(Error::TotalFeeAboveMax { fee, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the envelope output by `--build-only` or `--sim-only`, as base64 XDR or as a SEP-7
+    /// URI for a wallet to sign.
+    pub fn print_envelope(
+        &self,
+        tx_env: &TransactionEnvelope,
+        config: &config::Args,
+    ) -> Result<(), Error> {
+        if !self.sep7.sep7 {
+            println!("{}", tx_env.to_xdr_base64(Limits::none())?);
+            return Ok(());
+        }
+        let signing_key = self
+            .sep7
+            .sep7_signing_key
+            .as_ref()
+            .map(|key| -> Result<_, Error> { Ok(config.account(key)?.key_pair(None)?) })
+            .transpose()?;
+        let uri = self.sep7.uri(
+            tx_env,
+            &config.get_network()?.network_passphrase,
+            signing_key.as_ref(),
+        )?;
+        println!("{uri}");
+        if self.sep7.qr {
+            eprintln!("{}", sep7::qr_code(&uri)?);
+        }
+        Ok(())
+    }
+
+    /// Sequence number for a new transaction from the source account of `config`.
+    pub async fn next_sequence(
+        &self,
+        client: &Client,
+        config: &config::Args,
+    ) -> Result<i64, Error> {
+        let account = config.public_key()?;
+        let network_passphrase = &config.get_network()?.network_passphrase;
+        Ok(self
+            .sequence
+            .next(client, &account, network_passphrase)
+            .await?)
+    }
+
+    /// Sign and submit the assembled transaction as the source account of `config`, wrapped in a
+    /// fee bump signed by `--fee-source` if one was given. A transaction rejected because its
+    /// cached sequence number is out of date is resubmitted once with the account's current one.
+    /// Archived entries it uses are restored first with `--auto-restore`, and are an error
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Might return an error
+    pub async fn send_assembled_transaction(
+        &self,
+        client: &Client,
+        txn: Assembled,
+        signers: &[Signer],
+        config: &config::Args,
+        log_events: Option<LogEvents>,
+        log_resources: Option<LogResources>,
+    ) -> Result<GetTransactionResponse, Error> {
+        let key = config.signer()?;
+        let network_passphrase = &config.get_network()?.network_passphrase;
+        // The fees are checked before a restore is sent or the authorizations are signed, and
+        // again once they are
+        self.apply_to_simulated_txn(txn.transaction().clone())?;
+        let txn = if let Some(restore) = &txn.sim_response().restore_preamble {
+            if !self.auto_restore {
+                return Err(Error::ArchivedEntries(archived_entries(restore)?));
+            }
+            self.restore(
+                client,
+                txn.transaction(),
+                restore,
+                &key,
+                config,
+                network_passphrase,
+            )
+            .await?;
+            // Simulate the transaction again now that the entries are live, after the restore
+            // used up its sequence number
+            let mut tx = txn.transaction().clone();
+            tx.seq_num = SequenceNumber(tx.seq_num.0 + 1);
+            let txn = client.create_assembled_transaction(&tx).await?;
+            self.apply_to_simulated_txn(txn.transaction().clone())?;
+            txn
         } else {
-            add_padding_to_instructions(txn)
+            txn
+        };
+        let seq_num = txn.sim_response().latest_ledger + 60;
+        let authorized = authorize(client, txn, &key, signers, seq_num, network_passphrase).await?;
+        authorized.log(log_events, log_resources)?;
+        // Padded last, as signing the authorizations can simulate the transaction again
+        let tx = self.apply_to_simulated_txn(authorized.transaction().clone())?;
+        let (_, res) = self
+            .send_signed_by(client, tx, &key, config, network_passphrase)
+            .await?;
+        Ok(res)
+    }
+
+    /// Sign and submit a transaction that needs no simulation as the source account of
+    /// `config`, with the fee bump and sequence number handling of `send_assembled_transaction`.
+    /// Returns the hash of the envelope sent, which is that of the fee bump if there is one, and
+    /// reflects the sequence number of a resubmission.
+    pub async fn send_transaction(
+        &self,
+        client: &Client,
+        tx: Transaction,
+        config: &config::Args,
+    ) -> Result<([u8; 32], GetTransactionResponse), Error> {
+        let key = config.signer()?;
+        let network_passphrase = &config.get_network()?.network_passphrase;
+        self.send_signed_by(client, tx, &key, config, network_passphrase)
+            .await
+    }
+
+    async fn send_signed_by(
+        &self,
+        client: &Client,
+        mut tx: Transaction,
+        key: &Signer,
+        config: &config::Args,
+        network_passphrase: &str,
+    ) -> Result<([u8; 32], GetTransactionResponse), Error> {
+        let account = key.public_key();
+        match self
+            .sign_and_send(client, &tx, key, config, network_passphrase)
+            .await
+        {
+            Err(Error::Rpc(soroban_rpc::Error::TransactionSubmissionFailed(e)))
+                if self.sequence.can_resync() && e.contains("TxBadSeq") =>
+            {
+                tracing::warn!(
+                    "cached sequence number {} of {account} is out of date, fetching it again",
+                    tx.seq_num.0
+                );
+                tx.seq_num = SequenceNumber(
+                    self.sequence
+                        .resync(client, &account, network_passphrase)
+                        .await?,
+                );
+                self.sign_and_send(client, &tx, key, config, network_passphrase)
+                    .await
+            }
+            res => {
+                if res.is_ok() {
+                    // Covers the sequence number used up by a restore sent ahead of the transaction
+                    self.sequence
+                        .store(&account, network_passphrase, tx.seq_num.0)?;
+                }
+                res
+            }
+        }
+    }
+
+    /// Restore the archived entries of `parent`, with a transaction using its sequence number.
+    async fn restore(
+        &self,
+        client: &Client,
+        parent: &Transaction,
+        restore: &RestorePreamble,
+        key: &Signer,
+        config: &config::Args,
+        network_passphrase: &str,
+    ) -> Result<(), Error> {
+        let tx = restore_transaction(parent, restore, self.fee)?;
+        self.check_max_fees(&tx)?;
+        tracing::info!(
+            "Restoring {} archived ledger entries, for a fee of up to {} stroops",
+            archived_entries(restore)?,
+            tx.fee
+        );
+        let (_, res) = self
+            .sign_and_send(client, &tx, key, config, network_passphrase)
+            .await?;
+        if let Some(result) = &res.result {
+            tracing::info!("Restore fee charged: {} stroops", result.fee_charged);
         }
+        Ok(())
+    }
+
+    async fn sign_and_send(
+        &self,
+        client: &Client,
+        tx: &Transaction,
+        key: &Signer,
+        config: &config::Args,
+        network_passphrase: &str,
+    ) -> Result<([u8; 32], GetTransactionResponse), Error> {
+        let tx_env = key.sign_transaction(tx, network_passphrase)?;
+        let tx_env = if let Some(fee_source) = &self.fee_source {
+            let fee_key = config.account(fee_source)?.signer(None)?;
+            let fee_bump = utils::fee_bump_transaction(
+                &tx_env,
+                MuxedAccount::Ed25519(Uint256(fee_key.public_key().0)),
+                self.fee,
+            )?;
+            fee_key.sign_transaction_envelope(&fee_bump, network_passphrase)?
+        } else {
+            tx_env
+        };
+        let hash = utils::transaction_envelope_hash(&tx_env, network_passphrase)?;
+        Ok((hash, client.send_transaction(&tx_env).await?))
     }
 }
 
-pub fn add_padding_to_instructions(txn: Assembled) -> Assembled {
-    let xdr::TransactionExt::V1(xdr::SorobanTransactionData {
-        resources: xdr::SorobanResources { instructions, .. },
-        ..
-    }) = txn.transaction().ext
-    else {
-        return txn;
-    };
-    // Start with 150%
-    let instructions = (instructions.checked_mul(150 / 100)).unwrap_or(instructions);
-    txn.set_max_instructions(instructions)
+/// Sign the authorization entries of the transaction that are for an account address, with the
+/// signer of that account among `signers` or `source`, and simulate it again as verifying the
+/// signatures changes the resources it needs.
+async fn authorize(
+    client: &Client,
+    txn: Assembled,
+    source: &Signer,
+    signers: &[Signer],
+    signature_expiration_ledger: u32,
+    network_passphrase: &str,
+) -> Result<Assembled, Error> {
+    let mut tx = txn.transaction().clone();
+    let entries = utils::address_auth_entries(&tx);
+    if entries.is_empty() {
+        return Ok(txn);
+    }
+    let signed = entries
+        .iter()
+        .map(|entry| {
+            let SorobanCredentials::Address(credentials) = &entry.credentials else {
+                return Ok(entry.clone());
+            };
+            let account = utils::auth_entry_account(entry);
+            let signer = signers
+                .iter()
+                .chain([source])
+                .find(|signer| account.is_some_and(|account| account.0 == signer.public_key().0))
+                .ok_or_else(|| soroban_rpc::Error::MissingSignerForAddress {
+                    address: utils::sc_address_to_string(&credentials.address),
+                })?;
+            Ok(signer.sign_auth_entry(entry, signature_expiration_ledger, network_passphrase)?)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    utils::replace_auth_entries(&mut tx, &signed)?;
+    Ok(client.create_assembled_transaction(&tx).await?)
+}
+
+/// Transaction restoring the archived entries of `parent`, with its source and sequence number.
+fn restore_transaction(
+    parent: &Transaction,
+    restore: &RestorePreamble,
+    inclusion_fee: u32,
+) -> Result<Transaction, Error> {
+    let fee = u32::try_from(u64::from(inclusion_fee) + restore.min_resource_fee)
+        .map_err(|_| Error::LargeFee(restore.min_resource_fee))?;
+    Ok(Transaction {
+        source_account: parent.source_account.clone(),
+        fee,
+        seq_num: parent.seq_num.clone(),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![Operation {
+            source_account: None,
+            body: OperationBody::RestoreFootprint(RestoreFootprintOp {
+                ext: ExtensionPoint::V0,
+            }),
+        }]
+        .try_into()?,
+        ext: TransactionExt::V1(SorobanTransactionData::from_xdr_base64(
+            &restore.transaction_data,
+            Limits::none(),
+        )?),
+    })
+}
+
+fn archived_entries(restore: &RestorePreamble) -> Result<usize, Error> {
+    let data = SorobanTransactionData::from_xdr_base64(&restore.transaction_data, Limits::none())?;
+    Ok(data.resources.footprint.read_write.len())
+}
+
+/// `value` increased by `percent` percent.
+fn pad(value: u64, percent: u32) -> u64 {
+    value.saturating_mul(100 + u64::from(percent)) / 100
+}
+
+fn pad_u32(value: u32, percent: u32) -> u32 {
+    u32::try_from(pad(value.into(), percent)).unwrap_or(u32::MAX)
 }
 
 impl Default for Args {
@@ -54,8 +438,154 @@ impl Default for Args {
             fee: 100,
             cost: false,
             instructions: None,
+            instructions_padding: 0,
+            read_bytes_padding: 0,
+            write_bytes_padding: 0,
+            resource_fee_padding: 0,
+            max_resource_fee: None,
+            max_total_fee: None,
             build_only: false,
             sim_only: false,
+            fee_source: None,
+            auto_restore: false,
+            preconditions: preconditions::Args::default(),
+            memo: memo::Args::default(),
+            sequence: sequence::Args::default(),
+            sep7: sep7::Args::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_env_host::xdr::{
+        LedgerFootprint, LedgerKey, LedgerKeyContractCode, SorobanResources, VecM, WriteXdr,
+    };
+
+    #[test]
+    fn test_restore_transaction() {
+        let data = SorobanTransactionData {
+            ext: ExtensionPoint::V0,
+            resources: SorobanResources {
+                footprint: LedgerFootprint {
+                    read_only: VecM::default(),
+                    read_write: vec![LedgerKey::ContractCode(LedgerKeyContractCode {
+                        hash: xdr::Hash([1; 32]),
+                    })]
+                    .try_into()
+                    .unwrap(),
+                },
+                instructions: 0,
+                read_bytes: 100,
+                write_bytes: 100,
+            },
+            resource_fee: 5000,
+        };
+        let restore = RestorePreamble {
+            transaction_data: data.to_xdr_base64(Limits::none()).unwrap(),
+            min_resource_fee: 5000,
+        };
+        let parent = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([2; 32])),
+            fee: 90_000,
+            seq_num: SequenceNumber(7),
+            cond: Preconditions::None,
+            memo: Memo::Text("invoke".try_into().unwrap()),
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        };
+
+        let tx = restore_transaction(&parent, &restore, 100).unwrap();
+        assert_eq!(archived_entries(&restore).unwrap(), 1);
+        assert_eq!(tx.fee, 5100);
+        assert_eq!(tx.seq_num, parent.seq_num);
+        assert_eq!(tx.source_account, parent.source_account);
+        assert_eq!(tx.memo, Memo::None);
+        assert_eq!(tx.ext, TransactionExt::V1(data));
+    }
+
+    #[test]
+    fn test_apply_to_simulated_txn() {
+        let resources = SorobanResources {
+            footprint: LedgerFootprint {
+                read_only: VecM::default(),
+                read_write: VecM::default(),
+            },
+            instructions: 1_000_000,
+            read_bytes: 200,
+            write_bytes: 100,
+        };
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([2; 32])),
+            fee: 10_100,
+            seq_num: SequenceNumber(7),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V1(SorobanTransactionData {
+                ext: ExtensionPoint::V0,
+                resources,
+                resource_fee: 10_000,
+            }),
+        };
+
+        let unpadded = Args::default().apply_to_simulated_txn(tx.clone()).unwrap();
+        assert_eq!(unpadded, tx);
+
+        let padding = Args {
+            instructions_padding: 50,
+            write_bytes_padding: 10,
+            resource_fee_padding: 50,
+            ..Args::default()
+        };
+        let padded = padding.apply_to_simulated_txn(tx.clone()).unwrap();
+        let TransactionExt::V1(data) = &padded.ext else {
+            panic!("not a soroban transaction");
+        };
+        assert_eq!(data.resources.instructions, 1_500_000);
+        assert_eq!(data.resources.read_bytes, 200);
+        assert_eq!(data.resources.write_bytes, 110);
+        assert_eq!(data.resource_fee, 15_000);
+        assert_eq!(padded.fee, 15_100);
+
+        let exact = Args {
+            instructions: Some(2_000_000),
+            max_resource_fee: Some(10_000),
+            max_total_fee: Some(10_100),
+            ..Args::default()
+        }
+        .apply_to_simulated_txn(tx.clone())
+        .unwrap();
+        let TransactionExt::V1(data) = &exact.ext else {
+            panic!("not a soroban transaction");
+        };
+        assert_eq!(data.resources.instructions, 2_000_000);
+        assert_eq!(exact.fee, 10_100);
+
+        let res = Args {
+            max_resource_fee: Some(12_000),
+            ..padding.clone()
+        }
+        .apply_to_simulated_txn(tx.clone());
+        assert!(matches!(
+            res,
+            Err(Error::ResourceFeeAboveMax {
+                fee: 15_000,
+                max: 12_000
+            })
+        ));
+        let res = Args {
+            max_total_fee: Some(15_000),
+            ..padding
         }
+        .apply_to_simulated_txn(tx);
+        assert!(matches!(
+            res,
+            Err(Error::TotalFeeAboveMax {
+                fee: 15_100,
+                max: 15_000
+            })
+        ));
     }
 }
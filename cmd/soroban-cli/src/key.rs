@@ -6,8 +6,7 @@ use soroban_env_host::xdr::{
 use std::path::PathBuf;
 
 use crate::{
-    commands::contract::Durability,
-    utils::{self},
+    commands::{config::locator, contract::Durability},
     wasm,
 };
 
@@ -21,12 +20,14 @@ pub enum Error {
     CannotParseContractId(String, stellar_strkey::DecodeError),
     #[error(transparent)]
     Wasm(#[from] wasm::Error),
+    #[error(transparent)]
+    Locator(#[from] locator::Error),
 }
 
 #[derive(Debug, clap::Args, Clone)]
 #[group(skip)]
 pub struct Args {
-    /// Contract ID to which owns the data entries.
+    /// Contract ID, or its alias on the network, to which owns the data entries.
     /// If no keys provided the Contract's instance will be extended
     #[arg(
         long = "id",
@@ -64,7 +65,11 @@ pub struct Args {
 }
 
 impl Args {
-    pub fn parse_keys(&self) -> Result<Vec<LedgerKey>, Error> {
+    pub fn parse_keys(
+        &self,
+        locator: &locator::Args,
+        network_passphrase: &str,
+    ) -> Result<Vec<LedgerKey>, Error> {
         let keys = if let Some(keys) = &self.key {
             keys.iter()
                 .map(|key| {
@@ -90,7 +95,8 @@ impl Args {
         } else {
             vec![ScVal::LedgerKeyContractInstance]
         };
-        let contract_id = contract_id(self.contract_id.as_ref().unwrap())?;
+        let contract_id =
+            locator.resolve_contract_id(self.contract_id.as_ref().unwrap(), network_passphrase)?;
 
         Ok(keys
             .into_iter()
@@ -104,7 +110,3 @@ impl Args {
             .collect())
     }
 }
-
-fn contract_id(s: &str) -> Result<[u8; 32], Error> {
-    utils::contract_id_from_str(s).map_err(|e| Error::CannotParseContractId(s.to_string(), e))
-}
@@ -15,6 +15,11 @@ pub mod commands;
 pub mod fee;
 pub mod key;
 pub mod log;
+pub mod memo;
+pub mod preconditions;
+pub mod sep7;
+pub mod sequence;
+pub mod signer;
 pub mod toid;
 pub mod utils;
 pub mod wasm;
@@ -1,7 +1,207 @@
-use soroban_env_host::xdr::{SorobanAuthorizationEntry, VecM};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use soroban_env_host::xdr::{
+    ContractExecutable, CreateContractArgs, Hash, InvokeContractArgs, ScAddress, ScVal,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    SorobanCredentials, VecM,
+};
+use soroban_spec_tools::Spec;
+
+use crate::utils;
 
 pub fn auth(auth: &[VecM<SorobanAuthorizationEntry>]) {
     if !auth.is_empty() {
-        tracing::debug!("{auth:#?}");
+        tracing::debug!(
+            "Authorizations:\n{}",
+            auth_tree(auth.iter().flat_map(|a| a.iter()), &BTreeMap::new())
+        );
+    }
+}
+
+/// Readable tree of authorizations: each authorizing address, followed by the contract calls it
+/// authorizes. Their arguments are named and decoded with the spec of the contract if `specs` has
+/// it, and printed as raw values otherwise.
+#[allow(clippy::module_name_repetitions)]
+pub fn auth_tree<'a>(
+    entries: impl IntoIterator<Item = &'a SorobanAuthorizationEntry>,
+    specs: &BTreeMap<Hash, Spec>,
+) -> String {
+    let mut tree = String::new();
+    for entry in entries {
+        let _ = match &entry.credentials {
+            SorobanCredentials::SourceAccount => writeln!(tree, "source account"),
+            SorobanCredentials::Address(credentials) => {
+                writeln!(
+                    tree,
+                    "{}",
+                    utils::sc_address_to_string(&credentials.address)
+                )
+            }
+        };
+        write_invocation(&mut tree, &entry.root_invocation, specs, "", true);
+    }
+    tree
+}
+
+/// Contracts called by the authorized invocations, whose specs are needed to decode them.
+#[allow(clippy::module_name_repetitions)]
+pub fn auth_contracts<'a>(
+    entries: impl IntoIterator<Item = &'a SorobanAuthorizationEntry>,
+) -> Vec<Hash> {
+    fn walk(invocation: &SorobanAuthorizedInvocation, contracts: &mut Vec<Hash>) {
+        if let SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+            contract_address: ScAddress::Contract(hash),
+            ..
+        }) = &invocation.function
+        {
+            if !contracts.contains(hash) {
+                contracts.push(hash.clone());
+            }
+        }
+        for sub in invocation.sub_invocations.iter() {
+            walk(sub, contracts);
+        }
+    }
+    let mut contracts = Vec::new();
+    for entry in entries {
+        walk(&entry.root_invocation, &mut contracts);
+    }
+    contracts
+}
+
+fn write_invocation(
+    tree: &mut String,
+    invocation: &SorobanAuthorizedInvocation,
+    specs: &BTreeMap<Hash, Spec>,
+    prefix: &str,
+    last: bool,
+) {
+    let (branch, indent) = if last {
+        ("└─", "   ")
+    } else {
+        ("├─", "│  ")
+    };
+    let _ = writeln!(
+        tree,
+        "{prefix}{branch} {}",
+        function(&invocation.function, specs)
+    );
+    let prefix = format!("{prefix}{indent}");
+    let count = invocation.sub_invocations.len();
+    for (i, sub) in invocation.sub_invocations.iter().enumerate() {
+        write_invocation(tree, sub, specs, &prefix, i + 1 == count);
+    }
+}
+
+fn function(function: &SorobanAuthorizedFunction, specs: &BTreeMap<Hash, Spec>) -> String {
+    match function {
+        SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+            contract_address,
+            function_name,
+            args,
+        }) => {
+            let name = function_name.to_utf8_string_lossy();
+            let spec = match contract_address {
+                ScAddress::Contract(hash) => specs.get(hash),
+                ScAddress::Account(_) => None,
+            };
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| argument(spec, &name, i, arg))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{}.{name}({args})",
+                utils::sc_address_to_string(contract_address)
+            )
+        }
+        SorobanAuthorizedFunction::CreateContractHostFn(CreateContractArgs {
+            executable, ..
+        }) => match executable {
+            ContractExecutable::Wasm(hash) => format!("create contract from wasm {hash}"),
+            ContractExecutable::StellarAsset => "create Stellar asset contract".to_string(),
+        },
+    }
+}
+
+fn argument(spec: Option<&Spec>, function: &str, i: usize, arg: &ScVal) -> String {
+    if let Some(spec) = spec {
+        let input = spec
+            .find_function(function)
+            .ok()
+            .and_then(|f| f.inputs.get(i).cloned());
+        if let Some(input) = input {
+            if let Ok(value) = spec.xdr_to_json(arg, &input.type_) {
+                return format!("{}: {value}", input.name.to_utf8_string_lossy());
+            }
+        }
+    }
+    soroban_spec_tools::to_string(arg).unwrap_or_else(|_| format!("{arg:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_env_host::xdr::{
+        AccountId, PublicKey, ScSpecEntry, ScSpecFunctionInputV0, ScSpecFunctionV0, ScSpecTypeDef,
+        SorobanAddressCredentials, Uint256,
+    };
+
+    #[test]
+    fn test_auth_tree() {
+        let token = Hash([1; 32]);
+        let swap = Hash([2; 32]);
+        let alice =
+            ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([3; 32]))));
+        let call = |contract: &Hash, function: &str, args: Vec<ScVal>| {
+            SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: ScAddress::Contract(contract.clone()),
+                function_name: function.try_into().unwrap(),
+                args: args.try_into().unwrap(),
+            })
+        };
+        let entry = SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                address: alice.clone(),
+                nonce: 0,
+                signature_expiration_ledger: 0,
+                signature: ScVal::Void,
+            }),
+            root_invocation: SorobanAuthorizedInvocation {
+                function: call(&swap, "swap", vec![ScVal::U32(5)]),
+                sub_invocations: vec![SorobanAuthorizedInvocation {
+                    function: call(&token, "transfer", vec![ScVal::Address(alice.clone())]),
+                    sub_invocations: VecM::default(),
+                }]
+                .try_into()
+                .unwrap(),
+            },
+        };
+        let swap_spec = Spec::new(vec![ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+            doc: "".try_into().unwrap(),
+            name: "swap".try_into().unwrap(),
+            inputs: vec![ScSpecFunctionInputV0 {
+                doc: "".try_into().unwrap(),
+                name: "amount".try_into().unwrap(),
+                type_: ScSpecTypeDef::U32,
+            }]
+            .try_into()
+            .unwrap(),
+            outputs: VecM::default(),
+        })]);
+        assert_eq!(auth_contracts([&entry]), [swap.clone(), token.clone()]);
+
+        let alice = utils::sc_address_to_string(&alice);
+        let tree = auth_tree([&entry], &BTreeMap::from([(swap.clone(), swap_spec)]));
+        assert_eq!(
+            tree,
+            format!(
+                "{alice}\n└─ {}.swap(amount: 5)\n   └─ {}.transfer(\"{alice}\")\n",
+                utils::sc_address_to_string(&ScAddress::Contract(swap)),
+                utils::sc_address_to_string(&ScAddress::Contract(token)),
+            )
+        );
     }
 }
@@ -0,0 +1,104 @@
+use clap::arg;
+use soroban_env_host::xdr::{Hash, Memo, StringM};
+
+use crate::commands::HEADING_RPC;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("memo text must be at most 28 bytes, got {0}")]
+    TextTooLong(usize),
+    #[error("memo hash must be 32 bytes of hex, got {0:?}")]
+    InvalidHash(String),
+}
+
+#[derive(Debug, clap::Args, Clone, Default)]
+#[group(skip)]
+pub struct Args {
+    /// Text memo of up to 28 bytes
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with_all = ["memo_id", "memo_hash", "memo_return"])]
+    pub memo_text: Option<String>,
+    /// Unsigned 64-bit integer memo, e.g. an exchange deposit ID
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with_all = ["memo_hash", "memo_return"])]
+    pub memo_id: Option<u64>,
+    /// Hash memo, as 32 bytes of hex
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with = "memo_return")]
+    pub memo_hash: Option<String>,
+    /// Hash of the transaction being refunded, as 32 bytes of hex
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub memo_return: Option<String>,
+}
+
+impl Args {
+    pub fn memo(&self) -> Result<Memo, Error> {
+        Ok(if let Some(text) = &self.memo_text {
+            Memo::Text(
+                StringM::try_from(text.as_bytes()).map_err(|_| Error::TextTooLong(text.len()))?,
+            )
+        } else if let Some(id) = self.memo_id {
+            Memo::Id(id)
+        } else if let Some(hash) = &self.memo_hash {
+            Memo::Hash(parse_hash(hash)?)
+        } else if let Some(hash) = &self.memo_return {
+            Memo::Return(parse_hash(hash)?)
+        } else {
+            Memo::None
+        })
+    }
+}
+
+fn parse_hash(s: &str) -> Result<Hash, Error> {
+    hex::decode(s)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(Hash)
+        .ok_or_else(|| Error::InvalidHash(s.to_string()))
+}
+
+/// Human readable form of a memo, e.g. `text "deposit"` or `id 42`.
+pub fn to_string(memo: &Memo) -> String {
+    match memo {
+        Memo::None => "none".to_string(),
+        Memo::Text(text) => format!("text {:?}", text.to_utf8_string_lossy()),
+        Memo::Id(id) => format!("id {id}"),
+        Memo::Hash(hash) => format!("hash {}", hex::encode(hash.0)),
+        Memo::Return(hash) => format!("return {}", hex::encode(hash.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo() {
+        assert_eq!(Args::default().memo().unwrap(), Memo::None);
+        let memo = Args {
+            memo_text: Some("deposit 1234".to_string()),
+            ..Default::default()
+        }
+        .memo()
+        .unwrap();
+        assert_eq!(to_string(&memo), "text \"deposit 1234\"");
+        assert!(matches!(
+            Args {
+                memo_text: Some("a".repeat(29)),
+                ..Default::default()
+            }
+            .memo(),
+            Err(Error::TextTooLong(29))
+        ));
+        let memo = Args {
+            memo_hash: Some("01".repeat(32)),
+            ..Default::default()
+        }
+        .memo()
+        .unwrap();
+        assert_eq!(memo, Memo::Hash(Hash([1; 32])));
+        assert!(Args {
+            memo_return: Some("01".repeat(31)),
+            ..Default::default()
+        }
+        .memo()
+        .is_err());
+    }
+}
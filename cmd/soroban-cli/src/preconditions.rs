@@ -0,0 +1,172 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::arg;
+use soroban_env_host::xdr::{
+    Duration, LedgerBounds, Preconditions, PreconditionsV2, SequenceNumber, TimeBounds, TimePoint,
+    VecM,
+};
+
+use crate::commands::HEADING_RPC;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("--valid-after {after} must be before --valid-before {before}")]
+    InvalidTimeBounds { after: u64, before: u64 },
+    #[error("--min-ledger {min} must be before --max-ledger {max}")]
+    InvalidLedgerBounds { min: u32, max: u32 },
+    #[error("system clock is before the unix epoch")]
+    InvalidSystemTime,
+}
+
+#[derive(Debug, clap::Args, Clone, Default)]
+#[group(skip)]
+pub struct Args {
+    /// Number of seconds from now after which the transaction is no longer valid
+    #[arg(long, help_heading = HEADING_RPC, conflicts_with = "valid_before")]
+    pub timeout: Option<u64>,
+    /// Unix timestamp, in seconds, before which the transaction is not valid
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub valid_after: Option<u64>,
+    /// Unix timestamp, in seconds, from which the transaction is no longer valid
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub valid_before: Option<u64>,
+    /// Ledger sequence before which the transaction is not valid
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub min_ledger: Option<u32>,
+    /// Ledger sequence from which the transaction is no longer valid
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub max_ledger: Option<u32>,
+    /// Minimum sequence number the source account must have for the transaction to be valid. Without it the account's sequence number must be exactly one less than the transaction's
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub min_sequence: Option<i64>,
+    /// Number of seconds that must have passed since the source account's sequence number last changed
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub min_sequence_age: Option<u64>,
+    /// Number of ledgers that must have closed since the source account's sequence number last changed
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub min_sequence_ledger_gap: Option<u32>,
+}
+
+impl Args {
+    /// Preconditions for the transaction. Time bounds alone are encoded as `Preconditions::Time`,
+    /// any of the other bounds require `Preconditions::V2`.
+    pub fn preconditions(&self) -> Result<Preconditions, Error> {
+        let time_bounds = self.time_bounds()?;
+        let ledger_bounds = self.ledger_bounds()?;
+        if ledger_bounds.is_none()
+            && self.min_sequence.is_none()
+            && self.min_sequence_age.is_none()
+            && self.min_sequence_ledger_gap.is_none()
+        {
+            return Ok(time_bounds.map_or(Preconditions::None, Preconditions::Time));
+        }
+        Ok(Preconditions::V2(PreconditionsV2 {
+            time_bounds,
+            ledger_bounds,
+            min_seq_num: self.min_sequence.map(SequenceNumber),
+            min_seq_age: Duration(self.min_sequence_age.unwrap_or_default()),
+            min_seq_ledger_gap: self.min_sequence_ledger_gap.unwrap_or_default(),
+            extra_signers: VecM::default(),
+        }))
+    }
+
+    fn time_bounds(&self) -> Result<Option<TimeBounds>, Error> {
+        let valid_before = if let Some(timeout) = self.timeout {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| Error::InvalidSystemTime)?;
+            Some(now.as_secs().saturating_add(timeout))
+        } else {
+            self.valid_before
+        };
+        if valid_before.is_none() && self.valid_after.is_none() {
+            return Ok(None);
+        }
+        let min_time = self.valid_after.unwrap_or_default();
+        // A max time of 0 means the transaction never expires
+        let max_time = valid_before.unwrap_or_default();
+        if max_time != 0 && min_time >= max_time {
+            return Err(Error::InvalidTimeBounds {
+                after: min_time,
+                before: max_time,
+            });
+        }
+        Ok(Some(TimeBounds {
+            min_time: TimePoint(min_time),
+            max_time: TimePoint(max_time),
+        }))
+    }
+
+    fn ledger_bounds(&self) -> Result<Option<LedgerBounds>, Error> {
+        if self.min_ledger.is_none() && self.max_ledger.is_none() {
+            return Ok(None);
+        }
+        let min_ledger = self.min_ledger.unwrap_or_default();
+        // A max ledger of 0 means there is no upper bound
+        let max_ledger = self.max_ledger.unwrap_or_default();
+        if max_ledger != 0 && min_ledger >= max_ledger {
+            return Err(Error::InvalidLedgerBounds {
+                min: min_ledger,
+                max: max_ledger,
+            });
+        }
+        Ok(Some(LedgerBounds {
+            min_ledger,
+            max_ledger,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preconditions() {
+        assert_eq!(
+            Args::default().preconditions().unwrap(),
+            Preconditions::None
+        );
+        assert_eq!(
+            Args {
+                valid_after: Some(10),
+                valid_before: Some(20),
+                ..Default::default()
+            }
+            .preconditions()
+            .unwrap(),
+            Preconditions::Time(TimeBounds {
+                min_time: TimePoint(10),
+                max_time: TimePoint(20),
+            })
+        );
+        let Preconditions::V2(v2) = Args {
+            timeout: Some(30),
+            max_ledger: Some(100),
+            min_sequence: Some(5),
+            ..Default::default()
+        }
+        .preconditions()
+        .unwrap() else {
+            panic!("expected v2 preconditions");
+        };
+        assert!(v2.time_bounds.unwrap().max_time.0 > 30);
+        assert_eq!(
+            v2.ledger_bounds,
+            Some(LedgerBounds {
+                min_ledger: 0,
+                max_ledger: 100,
+            })
+        );
+        assert_eq!(v2.min_seq_num, Some(SequenceNumber(5)));
+        assert!(matches!(
+            Args {
+                min_ledger: Some(100),
+                max_ledger: Some(100),
+                ..Default::default()
+            }
+            .preconditions(),
+            Err(Error::InvalidLedgerBounds { .. })
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use clap::arg;
+use ed25519_dalek::{Signer, SigningKey};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use qrcode::{render::unicode::Dense1x2, QrCode};
+use soroban_env_host::xdr::{self, Limits, TransactionEnvelope, WriteXdr};
+
+use crate::commands::HEADING_RPC;
+
+/// Passphrase wallets assume when a request names no network.
+const PUBLIC_NETWORK_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// Characters left as is in the values of the URI, the unreserved ones of RFC 3986.
+const VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("SEP-7 message must be at most 300 characters, got {0}")]
+    MessageTooLong(usize),
+    #[error("transaction is too large for a QR code: {0}")]
+    QrCode(#[from] qrcode::types::QrError),
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+}
+
+#[derive(Debug, clap::Args, Clone, Default)]
+#[group(skip)]
+pub struct Args {
+    /// With --build-only or --sim-only, output a SEP-7 `web+stellar:tx` URI for a wallet to sign the transaction, instead of its XDR. The network passphrase is included unless it is the public network's
+    #[arg(long, help_heading = HEADING_RPC, requires = "output_only")]
+    pub sep7: bool,
+    /// URL the wallet posts the signed transaction to, instead of submitting it to the network
+    #[arg(long, help_heading = HEADING_RPC, requires = "sep7")]
+    pub sep7_callback: Option<String>,
+    /// Message shown to the user by the wallet, of up to 300 characters
+    #[arg(long, help_heading = HEADING_RPC, requires = "sep7")]
+    pub sep7_msg: Option<String>,
+    /// Domain requesting the signature. Wallets check the URI's signature against the `URI_REQUEST_SIGNING_KEY` of its stellar.toml
+    #[arg(long, help_heading = HEADING_RPC, requires = "sep7", requires = "sep7_signing_key")]
+    pub sep7_origin_domain: Option<String>,
+    /// Key signing the URI for --sep7-origin-domain. Can be an identity or a secret key
+    #[arg(long, help_heading = HEADING_RPC, requires = "sep7_origin_domain")]
+    pub sep7_signing_key: Option<String>,
+    /// Also print the SEP-7 URI as a QR code to stderr, for a mobile wallet to scan
+    #[arg(long, help_heading = HEADING_RPC, requires = "sep7")]
+    pub qr: bool,
+}
+
+impl Args {
+    /// URI requesting a wallet to sign `tx_env` on the network of `network_passphrase`. It is
+    /// signed with `signing_key` when an origin domain is given.
+    pub fn uri(
+        &self,
+        tx_env: &TransactionEnvelope,
+        network_passphrase: &str,
+        signing_key: Option<&SigningKey>,
+    ) -> Result<String, Error> {
+        let mut uri = format!(
+            "web+stellar:tx?xdr={}",
+            encode(&tx_env.to_xdr_base64(Limits::none())?)
+        );
+        if let Some(callback) = &self.sep7_callback {
+            uri.push_str(&format!("&callback={}", encode(&format!("url:{callback}"))));
+        }
+        if let Some(msg) = &self.sep7_msg {
+            let len = msg.chars().count();
+            if len > 300 {
+                return Err(Error::MessageTooLong(len));
+            }
+            uri.push_str(&format!("&msg={}", encode(msg)));
+        }
+        if network_passphrase != PUBLIC_NETWORK_PASSPHRASE {
+            uri.push_str(&format!(
+                "&network_passphrase={}",
+                encode(network_passphrase)
+            ));
+        }
+        if let Some(origin_domain) = &self.sep7_origin_domain {
+            uri.push_str(&format!("&origin_domain={}", encode(origin_domain)));
+            if let Some(key) = signing_key {
+                let signature = key.sign(&signature_payload(&uri));
+                uri.push_str(&format!(
+                    "&signature={}",
+                    encode(&BASE64.encode(signature.to_bytes()))
+                ));
+            }
+        }
+        Ok(uri)
+    }
+}
+
+/// Bytes signed for the `signature` of a URI: 35 zero bytes and 4, as for other signed Stellar
+/// payloads, followed by the SEP-7 prefix and the URI without its signature.
+fn signature_payload(uri: &str) -> Vec<u8> {
+    let mut payload = vec![0; 35];
+    payload.push(4);
+    payload.extend_from_slice(b"stellar.sep.7 - URI Scheme");
+    payload.extend_from_slice(uri.as_bytes());
+    payload
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, VALUE).to_string()
+}
+
+/// QR code of `uri`, drawn with unicode blocks for a terminal.
+pub fn qr_code(uri: &str) -> Result<String, Error> {
+    Ok(QrCode::new(uri.as_bytes())?
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Verifier;
+    use soroban_env_host::xdr::{
+        Memo, MuxedAccount, Preconditions, SequenceNumber, Transaction, TransactionExt,
+        TransactionV1Envelope, Uint256, VecM,
+    };
+
+    #[test]
+    fn test_uri() {
+        let tx_env = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: Transaction {
+                source_account: MuxedAccount::Ed25519(Uint256([9; 32])),
+                fee: 100,
+                seq_num: SequenceNumber(1),
+                cond: Preconditions::None,
+                memo: Memo::None,
+                operations: VecM::default(),
+                ext: TransactionExt::V0,
+            },
+            signatures: VecM::default(),
+        });
+        let xdr = encode(&tx_env.to_xdr_base64(Limits::none()).unwrap());
+        assert!(!xdr.contains(['+', '/', '=']));
+
+        let args = Args {
+            sep7: true,
+            sep7_callback: Some("https://example.com/sign?id=1".to_string()),
+            sep7_msg: Some("Approve the deposit".to_string()),
+            ..Args::default()
+        };
+        assert_eq!(
+            args.uri(&tx_env, PUBLIC_NETWORK_PASSPHRASE, None).unwrap(),
+            format!("web+stellar:tx?xdr={xdr}&callback=url%3Ahttps%3A%2F%2Fexample.com%2Fsign%3Fid%3D1&msg=Approve%20the%20deposit")
+        );
+
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let args = Args {
+            sep7: true,
+            sep7_origin_domain: Some("example.com".to_string()),
+            ..Args::default()
+        };
+        let uri = args
+            .uri(&tx_env, "Test SDF Network ; September 2015", Some(&key))
+            .unwrap();
+        let (unsigned, signature) = uri.split_once("&signature=").unwrap();
+        assert_eq!(
+            unsigned,
+            format!("web+stellar:tx?xdr={xdr}&network_passphrase=Test%20SDF%20Network%20%3B%20September%202015&origin_domain=example.com")
+        );
+        let signature = percent_encoding::percent_decode_str(signature)
+            .decode_utf8()
+            .unwrap();
+        let signature: [u8; 64] = BASE64
+            .decode(signature.as_bytes())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        key.verifying_key()
+            .verify(
+                &signature_payload(unsigned),
+                &ed25519_dalek::Signature::from_bytes(&signature),
+            )
+            .unwrap();
+
+        let args = Args {
+            sep7: true,
+            sep7_msg: Some("a".repeat(301)),
+            ..Args::default()
+        };
+        assert!(matches!(
+            args.uri(&tx_env, PUBLIC_NETWORK_PASSPHRASE, None),
+            Err(Error::MessageTooLong(301))
+        ));
+        assert!(qr_code(&uri).is_ok());
+    }
+}
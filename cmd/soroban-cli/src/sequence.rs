@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use clap::arg;
+use sha2::{Digest, Sha256};
+use soroban_rpc::Client;
+
+use crate::commands::{config::data, HEADING_RPC};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Rpc(#[from] soroban_rpc::Error),
+    #[error(transparent)]
+    Data(#[from] data::Error),
+    #[error("cached sequence number {path:?}: {error}")]
+    Cache {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+#[derive(Debug, clap::Args, Clone, Default)]
+#[group(skip)]
+pub struct Args {
+    /// Sequence number of the transaction, instead of one more than the source account's. Combined with --build-only the transaction is built without any network request
+    #[arg(long, help_heading = HEADING_RPC)]
+    pub sequence: Option<i64>,
+    /// Remember the last sequence number used by each source account, so that consecutive transactions don't fetch it from the network. It is fetched again when a transaction fails with a bad sequence number
+    #[arg(long, env = "STELLAR_CACHE_SEQUENCE", help_heading = HEADING_RPC)]
+    pub cache_sequence: bool,
+}
+
+impl Args {
+    /// Sequence number for a new transaction from `account`: `--sequence` if given, otherwise one
+    /// more than the account's cached or current sequence number. The number is recorded in the
+    /// cache when `--cache-sequence` is set.
+    pub async fn next(
+        &self,
+        client: &Client,
+        account: &stellar_strkey::ed25519::PublicKey,
+        network_passphrase: &str,
+    ) -> Result<i64, Error> {
+        if let Some(sequence) = self.sequence {
+            return Ok(sequence);
+        }
+        if self.cache_sequence {
+            let path = cache_path(&data::sequences_dir()?, account, network_passphrase);
+            if let Some(cached) = read(&path)? {
+                tracing::debug!("using cached sequence number {cached} of {account}");
+                write(&path, cached + 1)?;
+                return Ok(cached + 1);
+            }
+        }
+        self.resync(client, account, network_passphrase).await
+    }
+
+    /// Whether a bad sequence number is due to a stale cache and should be fixed by a resync.
+    pub fn can_resync(&self) -> bool {
+        self.cache_sequence && self.sequence.is_none()
+    }
+
+    /// Like `next`, but always fetches the account's current sequence number from the network.
+    pub async fn resync(
+        &self,
+        client: &Client,
+        account: &stellar_strkey::ed25519::PublicKey,
+        network_passphrase: &str,
+    ) -> Result<i64, Error> {
+        let account_details = client.get_account(&account.to_string()).await?;
+        let sequence = i64::from(account_details.seq_num) + 1;
+        self.store(account, network_passphrase, sequence)?;
+        Ok(sequence)
+    }
+
+    /// Record `sequence` as the last one used by `account`, if `--cache-sequence` is set.
+    pub fn store(
+        &self,
+        account: &stellar_strkey::ed25519::PublicKey,
+        network_passphrase: &str,
+        sequence: i64,
+    ) -> Result<(), Error> {
+        if self.cache_sequence {
+            write(
+                &cache_path(&data::sequences_dir()?, account, network_passphrase),
+                sequence,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sequence numbers are cached per network, since the same key can be used on several of them.
+fn cache_path(
+    dir: &Path,
+    account: &stellar_strkey::ed25519::PublicKey,
+    network_passphrase: &str,
+) -> PathBuf {
+    let network_id = hex::encode(Sha256::digest(network_passphrase.as_bytes()));
+    dir.join(format!("{network_id}-{account}"))
+}
+
+fn read(path: &Path) -> Result<Option<i64>, Error> {
+    match std::fs::read_to_string(path) {
+        // An unparsable cache is treated as missing, and overwritten by the fetched sequence
+        Ok(contents) => Ok(contents.trim().parse().ok()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(Error::Cache {
+            path: path.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+fn write(path: &Path, sequence: i64) -> Result<(), Error> {
+    std::fs::write(path, sequence.to_string()).map_err(|error| Error::Cache {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = stellar_strkey::ed25519::PublicKey([1; 32]);
+        let testnet = cache_path(dir.path(), &account, "Test SDF Network ; September 2015");
+        let futurenet = cache_path(
+            dir.path(),
+            &account,
+            "Test SDF Future Network ; October 2022",
+        );
+        assert_ne!(testnet, futurenet);
+
+        assert_eq!(read(&testnet).unwrap(), None);
+        write(&testnet, 42).unwrap();
+        assert_eq!(read(&testnet).unwrap(), Some(42));
+        assert_eq!(read(&futurenet).unwrap(), None);
+
+        std::fs::write(&futurenet, "garbage").unwrap();
+        assert_eq!(read(&futurenet).unwrap(), None);
+    }
+}
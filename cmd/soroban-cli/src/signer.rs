@@ -0,0 +1,410 @@
+use std::{
+    io::Write,
+    process::{Command, ExitStatus, Stdio},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signer as _, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use soroban_env_host::xdr::{
+    self, DecoratedSignature, Signature, SignatureHint, SorobanAuthorizationEntry, Transaction,
+    TransactionEnvelope, TransactionV1Envelope, VecM,
+};
+use stellar_strkey::ed25519::PublicKey;
+
+use crate::utils;
+
+/// Prefix of the signers run as an external executable.
+pub const COMMAND_PREFIX: &str = "cmd:";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("unsupported signer {0:?}, expected `{COMMAND_PREFIX}` followed by the path of an executable")]
+    Unsupported(String),
+    #[error("running external signer {command:?}: {error}")]
+    CannotRun {
+        command: String,
+        error: std::io::Error,
+    },
+    #[error("external signer {command:?} failed with {status}")]
+    Failed { command: String, status: ExitStatus },
+    #[error("invalid response from external signer {command:?}: {error}")]
+    InvalidResponse {
+        command: String,
+        error: serde_json::Error,
+    },
+    #[error("external signer {command:?} returned an invalid public key {public_key:?}")]
+    InvalidPublicKey { command: String, public_key: String },
+    #[error("external signer {command:?} returned no signature")]
+    MissingSignature { command: String },
+    #[error("external signer {command:?} signed with {actual} instead of {expected}")]
+    WrongKey {
+        command: String,
+        expected: PublicKey,
+        actual: String,
+    },
+    #[error("external signer {command:?} returned an invalid signature")]
+    InvalidSignature { command: String },
+    #[error("xdr processing error: {0}")]
+    Xdr(#[from] xdr::Error),
+}
+
+/// Key signing transactions and authorization entries: either a secret key held by the CLI, or an
+/// external executable such as the bridge to an HSM, a cloud KMS or a hardware wallet.
+#[derive(Debug, Clone)]
+pub enum Signer {
+    Local(SigningKey),
+    External(External),
+}
+
+impl From<SigningKey> for Signer {
+    fn from(key: SigningKey) -> Self {
+        Signer::Local(key)
+    }
+}
+
+impl Signer {
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            Signer::Local(key) => PublicKey(key.verifying_key().to_bytes()),
+            Signer::External(external) => external.public_key,
+        }
+    }
+
+    /// Sign the transaction, as the envelope's only signature.
+    pub fn sign_transaction(
+        &self,
+        tx: &Transaction,
+        network_passphrase: &str,
+    ) -> Result<TransactionEnvelope, Error> {
+        self.sign_transaction_envelope(
+            &TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: tx.clone(),
+                signatures: VecM::default(),
+            }),
+            network_passphrase,
+        )
+    }
+
+    /// Sign the envelope, appending the signature to the ones already present.
+    pub fn sign_transaction_envelope(
+        &self,
+        tx_env: &TransactionEnvelope,
+        network_passphrase: &str,
+    ) -> Result<TransactionEnvelope, Error> {
+        let payload = utils::transaction_envelope_signature_payload(tx_env, network_passphrase)?;
+        let signature = self.sign(Kind::Transaction, &payload, network_passphrase)?;
+        let [.., a, b, c, d] = self.public_key().0;
+        Ok(utils::add_signatures(
+            tx_env,
+            &[DecoratedSignature {
+                hint: SignatureHint([a, b, c, d]),
+                signature: Signature(signature.to_vec().try_into()?),
+            }],
+        )?)
+    }
+
+    /// Sign an authorization entry, the signature being valid until
+    /// `signature_expiration_ledger`. Entries authorized by the source account are returned as is.
+    pub fn sign_auth_entry(
+        &self,
+        entry: &SorobanAuthorizationEntry,
+        signature_expiration_ledger: u32,
+        network_passphrase: &str,
+    ) -> Result<SorobanAuthorizationEntry, Error> {
+        let Some(payload) = utils::auth_entry_signature_payload(
+            entry,
+            signature_expiration_ledger,
+            network_passphrase,
+        )?
+        else {
+            return Ok(entry.clone());
+        };
+        let signature = self.sign(Kind::AuthEntry, &payload, network_passphrase)?;
+        Ok(utils::set_auth_entry_signature(
+            entry,
+            &self.public_key().0,
+            &signature,
+            signature_expiration_ledger,
+        )?)
+    }
+
+    /// Signature of the SHA-256 hash of `payload`.
+    fn sign(
+        &self,
+        kind: Kind,
+        payload: &[u8],
+        network_passphrase: &str,
+    ) -> Result<[u8; 64], Error> {
+        let hash: [u8; 32] = Sha256::digest(payload).into();
+        match self {
+            Signer::Local(key) => Ok(key.sign(&hash).to_bytes()),
+            Signer::External(external) => external.sign(kind, &hash, payload, network_passphrase),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Transaction,
+    AuthEntry,
+}
+
+/// Executable signing on behalf of the CLI. For each request it is run once, with a JSON request
+/// written to its stdin, and writes a JSON response to its stdout:
+///
+/// - `{"type": "public_key"}` is answered with `{"public_key": "G…"}`.
+/// - `{"type": "sign_transaction" | "sign_auth_entry", "network_passphrase": "…", "hash": "<hex>",
+///   "payload": "<base64>"}` is answered with `{"public_key": "G…", "signature": "<hex>"}`,
+///   the ed25519 signature of `hash`. `payload` is the XDR whose SHA-256 is `hash`, a
+///   `TransactionSignaturePayload` or a `HashIdPreimage`, for signers that show what they sign.
+///
+/// Its stderr is left attached to the terminal, so that it can prompt for a confirmation.
+#[derive(Debug, Clone)]
+pub struct External {
+    command: String,
+    public_key: PublicKey,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    PublicKey,
+    SignTransaction {
+        network_passphrase: &'a str,
+        hash: String,
+        payload: String,
+    },
+    SignAuthEntry {
+        network_passphrase: &'a str,
+        hash: String,
+        payload: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct Response {
+    public_key: String,
+    signature: Option<String>,
+}
+
+impl External {
+    /// Signer for `signer`, `cmd:` followed by the path of the executable and any arguments
+    /// separated by spaces. The executable is asked for its public key right away.
+    pub fn new(signer: &str) -> Result<Self, Error> {
+        let command = signer
+            .strip_prefix(COMMAND_PREFIX)
+            .filter(|command| !command.trim().is_empty())
+            .ok_or_else(|| Error::Unsupported(signer.to_string()))?
+            .to_string();
+        let response = run(&command, &Request::PublicKey)?;
+        let public_key =
+            PublicKey::from_string(&response.public_key).map_err(|_| Error::InvalidPublicKey {
+                command: command.clone(),
+                public_key: response.public_key.clone(),
+            })?;
+        Ok(Self {
+            command,
+            public_key,
+        })
+    }
+
+    fn sign(
+        &self,
+        kind: Kind,
+        hash: &[u8; 32],
+        payload: &[u8],
+        network_passphrase: &str,
+    ) -> Result<[u8; 64], Error> {
+        let (hex_hash, payload) = (hex::encode(hash), BASE64.encode(payload));
+        let request = match kind {
+            Kind::Transaction => Request::SignTransaction {
+                network_passphrase,
+                hash: hex_hash,
+                payload,
+            },
+            Kind::AuthEntry => Request::SignAuthEntry {
+                network_passphrase,
+                hash: hex_hash,
+                payload,
+            },
+        };
+        let response = run(&self.command, &request)?;
+        if response.public_key != self.public_key.to_string() {
+            return Err(Error::WrongKey {
+                command: self.command.clone(),
+                expected: self.public_key,
+                actual: response.public_key,
+            });
+        }
+        let invalid = || Error::InvalidSignature {
+            command: self.command.clone(),
+        };
+        let signature: [u8; 64] =
+            hex::decode(response.signature.ok_or_else(|| Error::MissingSignature {
+                command: self.command.clone(),
+            })?)
+            .map_err(|_| invalid())?
+            .try_into()
+            .map_err(|_| invalid())?;
+        // A wrong signature would only be reported by the network, without saying whose it is
+        VerifyingKey::from_bytes(&self.public_key.0)
+            .and_then(|key| key.verify(hash, &ed25519_dalek::Signature::from_bytes(&signature)))
+            .map_err(|_| invalid())?;
+        Ok(signature)
+    }
+}
+
+fn run(command: &str, request: &Request) -> Result<Response, Error> {
+    let cannot_run = |error| Error::CannotRun {
+        command: command.to_string(),
+        error,
+    };
+    let mut args = command.split_whitespace();
+    let program = args.next().unwrap_or_default();
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(cannot_run)?;
+    let body = serde_json::to_vec(request).expect("requests serialize");
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&body).map_err(cannot_run)?;
+    }
+    let output = child.wait_with_output().map_err(cannot_run)?;
+    if !output.status.success() {
+        return Err(Error::Failed {
+            command: command.to_string(),
+            status: output.status,
+        });
+    }
+    serde_json::from_slice(&output.stdout).map_err(|error| Error::InvalidResponse {
+        command: command.to_string(),
+        error,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, path::Path};
+
+    use super::*;
+    use soroban_env_host::xdr::{
+        Memo, MuxedAccount, Preconditions, SequenceNumber, TransactionExt, Uint256,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    fn tx(seq_num: i64) -> Transaction {
+        Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([9; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(seq_num),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        }
+    }
+
+    /// Stub signer recording its request, and answering with a fixed response.
+    fn stub(dir: &Path, response: &str, exit: u8) -> String {
+        let path = dir.join("signer");
+        let request = dir.join("request.json");
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\ncat > {}\necho '{response}'\nexit {exit}\n",
+                request.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        format!("{COMMAND_PREFIX}{}", path.display())
+    }
+
+    #[test]
+    fn test_external_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let local = Signer::Local(key.clone());
+        let signed = local.sign_transaction(&tx(1), PASSPHRASE).unwrap();
+        let signature = hex::encode(&utils::envelope_signatures(&signed)[0].signature.0);
+        let public_key = local.public_key();
+
+        let external = Signer::External(
+            External::new(&stub(
+                dir.path(),
+                &format!(r#"{{"public_key": "{public_key}", "signature": "{signature}"}}"#),
+                0,
+            ))
+            .unwrap(),
+        );
+        assert_eq!(external.public_key(), public_key);
+        assert_eq!(
+            external.sign_transaction(&tx(1), PASSPHRASE).unwrap(),
+            signed
+        );
+        let request: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("request.json")).unwrap())
+                .unwrap();
+        assert_eq!(request["type"], "sign_transaction");
+        assert_eq!(request["network_passphrase"], PASSPHRASE);
+        assert_eq!(
+            request["hash"],
+            hex::encode(utils::transaction_hash(&tx(1), PASSPHRASE).unwrap())
+        );
+
+        // The stub's signature is for another transaction
+        assert!(matches!(
+            external.sign_transaction(&tx(2), PASSPHRASE),
+            Err(Error::InvalidSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sign_transaction_envelope_appends() {
+        let alice = Signer::Local(SigningKey::from_bytes(&[1; 32]));
+        let bob = Signer::Local(SigningKey::from_bytes(&[2; 32]));
+        let alice_signed = alice.sign_transaction(&tx(1), PASSPHRASE).unwrap();
+        let bob_signed = bob.sign_transaction(&tx(1), PASSPHRASE).unwrap();
+
+        let tx_env = bob
+            .sign_transaction_envelope(&alice_signed, PASSPHRASE)
+            .unwrap();
+        let tx_env = alice
+            .sign_transaction_envelope(&tx_env, PASSPHRASE)
+            .unwrap();
+        assert_eq!(
+            utils::envelope_signatures(&tx_env),
+            [
+                utils::envelope_signatures(&alice_signed)[0].clone(),
+                utils::envelope_signatures(&bob_signed)[0].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_signer_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            External::new("/usr/local/bin/signer"),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            External::new(&stub(dir.path(), "{}", 1)),
+            Err(Error::Failed { .. })
+        ));
+        assert!(matches!(
+            External::new(&stub(dir.path(), "not json", 0)),
+            Err(Error::InvalidResponse { .. })
+        ));
+        assert!(matches!(
+            External::new(&stub(dir.path(), r#"{"public_key": "G"}"#, 0)),
+            Err(Error::InvalidPublicKey { .. })
+        ));
+    }
+}
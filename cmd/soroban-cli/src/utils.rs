@@ -1,12 +1,15 @@
-use ed25519_dalek::Signer;
 use sha2::{Digest, Sha256};
 use stellar_strkey::ed25519::PrivateKey;
 
 use soroban_env_host::xdr::{
-    Asset, ContractIdPreimage, DecoratedSignature, Error as XdrError, Hash, HashIdPreimage,
-    HashIdPreimageContractId, Limits, Signature, SignatureHint, Transaction, TransactionEnvelope,
-    TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
-    TransactionV1Envelope, WriteXdr,
+    AccountId, Asset, ContractIdPreimage, DecoratedSignature, Error as XdrError,
+    FeeBumpTransaction, FeeBumpTransactionEnvelope, FeeBumpTransactionExt,
+    FeeBumpTransactionInnerTx, Hash, HashIdPreimage, HashIdPreimageContractId,
+    HashIdPreimageSorobanAuthorization, Limits, MuxedAccount, OperationBody, Preconditions,
+    PublicKey, ScAddress, ScMap, ScSymbol, ScVal, SorobanAddressCredentials,
+    SorobanAuthorizationEntry, SorobanCredentials, Transaction, TransactionEnvelope,
+    TransactionExt, TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV0, TransactionV1Envelope, Uint256, VecM, WriteXdr,
 };
 
 pub use soroban_spec_tools::contract as contract_spec;
@@ -22,33 +25,316 @@ pub fn contract_hash(contract: &[u8]) -> Result<Hash, XdrError> {
 ///
 /// Might return an error
 pub fn transaction_hash(tx: &Transaction, network_passphrase: &str) -> Result<[u8; 32], XdrError> {
-    let signature_payload = TransactionSignaturePayload {
+    signature_payload_hash(
+        TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+        network_passphrase,
+    )
+}
+
+/// Hash that the signatures of the envelope are made over. A `TransactionV0` is hashed as the
+/// equivalent `Transaction`, the same way the network does.
+///
+/// # Errors
+///
+/// Might return an error
+pub fn transaction_envelope_hash(
+    tx_env: &TransactionEnvelope,
+    network_passphrase: &str,
+) -> Result<[u8; 32], XdrError> {
+    Ok(Sha256::digest(transaction_envelope_signature_payload(
+        tx_env,
+        network_passphrase,
+    )?)
+    .into())
+}
+
+/// XDR of the `TransactionSignaturePayload` of the envelope, whose hash is signed.
+///
+/// # Errors
+///
+/// Might return an error
+pub fn transaction_envelope_signature_payload(
+    tx_env: &TransactionEnvelope,
+    network_passphrase: &str,
+) -> Result<Vec<u8>, XdrError> {
+    signature_payload(
+        match tx_env {
+            TransactionEnvelope::TxV0(env) => {
+                TransactionSignaturePayloadTaggedTransaction::Tx(transaction_from_v0(&env.tx))
+            }
+            TransactionEnvelope::Tx(env) => {
+                TransactionSignaturePayloadTaggedTransaction::Tx(env.tx.clone())
+            }
+            TransactionEnvelope::TxFeeBump(env) => {
+                TransactionSignaturePayloadTaggedTransaction::TxFeeBump(env.tx.clone())
+            }
+        },
+        network_passphrase,
+    )
+}
+
+fn signature_payload_hash(
+    tagged_transaction: TransactionSignaturePayloadTaggedTransaction,
+    network_passphrase: &str,
+) -> Result<[u8; 32], XdrError> {
+    Ok(Sha256::digest(signature_payload(tagged_transaction, network_passphrase)?).into())
+}
+
+fn signature_payload(
+    tagged_transaction: TransactionSignaturePayloadTaggedTransaction,
+    network_passphrase: &str,
+) -> Result<Vec<u8>, XdrError> {
+    TransactionSignaturePayload {
         network_id: Hash(Sha256::digest(network_passphrase).into()),
-        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+        tagged_transaction,
+    }
+    .to_xdr(Limits::none())
+}
+
+/// `Transaction` equivalent to a `TransactionV0`, as the network treats it.
+pub fn transaction_from_v0(tx: &TransactionV0) -> Transaction {
+    Transaction {
+        source_account: MuxedAccount::Ed25519(tx.source_account_ed25519.clone()),
+        fee: tx.fee,
+        seq_num: tx.seq_num.clone(),
+        cond: tx
+            .time_bounds
+            .clone()
+            .map_or(Preconditions::None, Preconditions::Time),
+        memo: tx.memo.clone(),
+        operations: tx.operations.clone(),
+        ext: TransactionExt::V0,
+    }
+}
+
+/// Signatures of the envelope. For a fee bump these are the signatures of the outer transaction.
+pub fn envelope_signatures(tx_env: &TransactionEnvelope) -> &[DecoratedSignature] {
+    match tx_env {
+        TransactionEnvelope::TxV0(env) => &env.signatures,
+        TransactionEnvelope::Tx(env) => &env.signatures,
+        TransactionEnvelope::TxFeeBump(env) => &env.signatures,
+    }
+}
+
+/// Append `signatures` to the envelope, skipping any it already has.
+///
+/// # Errors
+///
+/// Might return an error
+pub fn add_signatures(
+    tx_env: &TransactionEnvelope,
+    signatures: &[DecoratedSignature],
+) -> Result<TransactionEnvelope, XdrError> {
+    let mut tx_env = tx_env.clone();
+    let existing = signatures_mut(&mut tx_env);
+    let mut all = existing.to_vec();
+    for signature in signatures {
+        if !all.contains(signature) {
+            all.push(signature.clone());
+        }
+    }
+    *existing = all.try_into()?;
+    Ok(tx_env)
+}
+
+/// The envelope with all of its signatures removed.
+pub fn strip_signatures(tx_env: &TransactionEnvelope) -> TransactionEnvelope {
+    let mut tx_env = tx_env.clone();
+    *signatures_mut(&mut tx_env) = VecM::default();
+    tx_env
+}
+
+/// Wrap a signed transaction in an unsigned fee bump paid by `fee_source`. The outer fee covers
+/// the inner transaction's resource fee plus `fee` stroops for each operation and for the fee bump
+/// itself, raised if needed so that it never bids less than the inner transaction.
+///
+/// # Errors
+///
+/// Might return an error if the envelope already is a fee bump
+pub fn fee_bump_transaction(
+    tx_env: &TransactionEnvelope,
+    fee_source: MuxedAccount,
+    fee: u32,
+) -> Result<TransactionEnvelope, XdrError> {
+    let inner = match tx_env {
+        TransactionEnvelope::TxV0(env) => TransactionV1Envelope {
+            tx: transaction_from_v0(&env.tx),
+            signatures: env.signatures.clone(),
+        },
+        TransactionEnvelope::Tx(env) => env.clone(),
+        TransactionEnvelope::TxFeeBump(_) => return Err(XdrError::Invalid),
+    };
+    let resource_fee = match &inner.tx.ext {
+        TransactionExt::V0 => 0,
+        TransactionExt::V1(data) => data.resource_fee,
     };
-    Ok(Sha256::digest(signature_payload.to_xdr(Limits::none())?).into())
+    let operations =
+        i64::try_from(inner.tx.operations.len().max(1)).map_err(|_| XdrError::Invalid)?;
+    let inner_inclusion_fee = i64::from(inner.tx.fee) - resource_fee;
+    let inner_rate = (inner_inclusion_fee + operations - 1) / operations;
+    let rate = inner_rate.max(i64::from(fee));
+    Ok(TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+        tx: FeeBumpTransaction {
+            fee_source,
+            fee: resource_fee + rate * (operations + 1),
+            inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+            ext: FeeBumpTransactionExt::V0,
+        },
+        signatures: VecM::default(),
+    }))
+}
+
+fn signatures_mut(tx_env: &mut TransactionEnvelope) -> &mut VecM<DecoratedSignature, 20> {
+    match tx_env {
+        TransactionEnvelope::TxV0(env) => &mut env.signatures,
+        TransactionEnvelope::Tx(env) => &mut env.signatures,
+        TransactionEnvelope::TxFeeBump(env) => &mut env.signatures,
+    }
+}
+
+/// Account ID of a possibly muxed account.
+pub fn muxed_account_ed25519(account: &MuxedAccount) -> Uint256 {
+    match account {
+        MuxedAccount::Ed25519(key) => key.clone(),
+        MuxedAccount::MuxedEd25519(muxed) => muxed.ed25519.clone(),
+    }
 }
 
+/// Strkey of a possibly muxed account: a G-address, or an M-address carrying the mux id.
+pub fn muxed_account_to_string(account: &MuxedAccount) -> String {
+    match account {
+        MuxedAccount::Ed25519(Uint256(key)) => stellar_strkey::ed25519::PublicKey(*key).to_string(),
+        MuxedAccount::MuxedEd25519(muxed) => stellar_strkey::ed25519::MuxedAccount {
+            ed25519: muxed.ed25519.0,
+            id: muxed.id,
+        }
+        .to_string(),
+    }
+}
+
+/// Strkey of an address: a G-address for an account, or a C-address for a contract.
+pub fn sc_address_to_string(address: &ScAddress) -> String {
+    match address {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key)))) => {
+            stellar_strkey::ed25519::PublicKey(*key).to_string()
+        }
+        ScAddress::Contract(Hash(contract)) => stellar_strkey::Contract(*contract).to_string(),
+    }
+}
+
+/// Authorization entries of the transaction's invocation that are authorized by an address
+/// signature, rather than by the source account of the transaction.
+pub fn address_auth_entries(tx: &Transaction) -> Vec<SorobanAuthorizationEntry> {
+    tx.operations
+        .iter()
+        .filter_map(|op| match &op.body {
+            OperationBody::InvokeHostFunction(body) => Some(body.auth.iter()),
+            _ => None,
+        })
+        .flatten()
+        .filter(|entry| matches!(entry.credentials, SorobanCredentials::Address(_)))
+        .cloned()
+        .collect()
+}
+
+/// Ed25519 key of the account authorizing the entry, if it is authorized by an account address.
+pub fn auth_entry_account(entry: &SorobanAuthorizationEntry) -> Option<&Uint256> {
+    match &entry.credentials {
+        SorobanCredentials::Address(SorobanAddressCredentials {
+            address: ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(key))),
+            ..
+        }) => Some(key),
+        _ => None,
+    }
+}
+
+/// XDR of the `HashIdPreimage` of an authorization entry, whose hash is signed, or `None` if it
+/// is authorized by the source account of the transaction.
+///
 /// # Errors
 ///
 /// Might return an error
-pub fn sign_transaction(
-    key: &ed25519_dalek::SigningKey,
-    tx: &Transaction,
+pub fn auth_entry_signature_payload(
+    entry: &SorobanAuthorizationEntry,
+    signature_expiration_ledger: u32,
     network_passphrase: &str,
-) -> Result<TransactionEnvelope, XdrError> {
-    let tx_hash = transaction_hash(tx, network_passphrase)?;
-    let tx_signature = key.sign(&tx_hash);
+) -> Result<Option<Vec<u8>>, XdrError> {
+    let SorobanCredentials::Address(credentials) = &entry.credentials else {
+        return Ok(None);
+    };
+    Ok(Some(
+        HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+            network_id: Hash(Sha256::digest(network_passphrase.as_bytes()).into()),
+            invocation: entry.root_invocation.clone(),
+            nonce: credentials.nonce,
+            signature_expiration_ledger,
+        })
+        .to_xdr(Limits::none())?,
+    ))
+}
 
-    let decorated_signature = DecoratedSignature {
-        hint: SignatureHint(key.verifying_key().to_bytes()[28..].try_into()?),
-        signature: Signature(tx_signature.to_bytes().try_into()?),
+/// Authorization entry with the signature of `public_key`.
+///
+/// # Errors
+///
+/// Might return an error
+pub fn set_auth_entry_signature(
+    entry: &SorobanAuthorizationEntry,
+    public_key: &[u8; 32],
+    signature: &[u8; 64],
+    signature_expiration_ledger: u32,
+) -> Result<SorobanAuthorizationEntry, XdrError> {
+    let mut entry = entry.clone();
+    let SorobanCredentials::Address(credentials) = &mut entry.credentials else {
+        return Ok(entry);
     };
+    let map = ScMap::sorted_from(vec![
+        (
+            ScVal::Symbol(ScSymbol("public_key".try_into()?)),
+            ScVal::Bytes(public_key.to_vec().try_into()?),
+        ),
+        (
+            ScVal::Symbol(ScSymbol("signature".try_into()?)),
+            ScVal::Bytes(signature.to_vec().try_into()?),
+        ),
+    ])?;
+    credentials.signature = ScVal::Vec(Some(vec![ScVal::Map(Some(map))].try_into()?));
+    credentials.signature_expiration_ledger = signature_expiration_ledger;
+    Ok(entry)
+}
 
-    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
-        tx: tx.clone(),
-        signatures: vec![decorated_signature].try_into()?,
-    }))
+/// Replace the authorization entries of the transaction with the signed ones authorizing the same
+/// address with the same nonce. Returns the signed entries that matched none of the transaction.
+///
+/// # Errors
+///
+/// Might return an error
+pub fn replace_auth_entries(
+    tx: &mut Transaction,
+    signed: &[SorobanAuthorizationEntry],
+) -> Result<Vec<SorobanAuthorizationEntry>, XdrError> {
+    let mut unmatched = signed.to_vec();
+    let mut operations = tx.operations.to_vec();
+    for op in &mut operations {
+        let OperationBody::InvokeHostFunction(body) = &mut op.body else {
+            continue;
+        };
+        let mut auth = body.auth.to_vec();
+        for entry in &mut auth {
+            let SorobanCredentials::Address(credentials) = &entry.credentials else {
+                continue;
+            };
+            if let Some(i) = unmatched.iter().position(|s| {
+                matches!(&s.credentials, SorobanCredentials::Address(c)
+                    if c.address == credentials.address && c.nonce == credentials.nonce)
+            }) {
+                *entry = unmatched.remove(i);
+            }
+        }
+        body.auth = auth.try_into()?;
+    }
+    tx.operations = operations.try_into()?;
+    Ok(unmatched)
 }
 
 /// # Errors
@@ -68,6 +354,18 @@ pub fn contract_id_from_str(contract_id: &str) -> Result<[u8; 32], stellar_strke
     )
 }
 
+/// Contract ID written out in full, as a `C…` strkey or 64 hex characters. Unlike
+/// `contract_id_from_str`, shorter hex strings are not padded, so they can be aliases instead.
+pub fn exact_contract_id_from_str(contract_id: &str) -> Option<[u8; 32]> {
+    if let Ok(strkey) = stellar_strkey::Contract::from_string(contract_id) {
+        return Some(strkey.0);
+    }
+    if contract_id.len() != 64 {
+        return None;
+    }
+    hex::decode(contract_id).ok()?.try_into().ok()
+}
+
 /// # Errors
 /// May not find a config dir
 pub fn find_config_dir(mut pwd: std::path::PathBuf) -> std::io::Result<std::path::PathBuf> {
@@ -216,4 +514,142 @@ mod tests {
             Err(err) => panic!("Failed to parse contract id: {err}"),
         }
     }
+
+    #[test]
+    fn test_fee_bump_transaction() {
+        use soroban_env_host::xdr::{
+            ExtensionPoint, LedgerFootprint, Operation, OperationBody, SequenceNumber,
+            SorobanResources, SorobanTransactionData, Uint256,
+        };
+
+        let network_passphrase = "Test SDF Network ; September 2015";
+        let source = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let sponsor = ed25519_dalek::SigningKey::from_bytes(&[2; 32]);
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(source.verifying_key().to_bytes())),
+            fee: 1100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: soroban_env_host::xdr::Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::BumpSequence(soroban_env_host::xdr::BumpSequenceOp {
+                    bump_to: SequenceNumber(2),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V1(SorobanTransactionData {
+                ext: ExtensionPoint::V0,
+                resources: SorobanResources {
+                    footprint: LedgerFootprint {
+                        read_only: VecM::default(),
+                        read_write: VecM::default(),
+                    },
+                    instructions: 0,
+                    read_bytes: 0,
+                    write_bytes: 0,
+                },
+                resource_fee: 1000,
+            }),
+        };
+        let tx_env = crate::signer::Signer::Local(source)
+            .sign_transaction(&tx, network_passphrase)
+            .unwrap();
+        let fee_source = MuxedAccount::Ed25519(Uint256(sponsor.verifying_key().to_bytes()));
+
+        let bumped = fee_bump_transaction(&tx_env, fee_source.clone(), 500).unwrap();
+        let TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope { tx: fee_bump, .. }) =
+            &bumped
+        else {
+            panic!("expected a fee bump envelope");
+        };
+        assert_eq!(fee_bump.fee, 1000 + 500 * 2);
+        assert_eq!(
+            fee_bump.inner_tx,
+            FeeBumpTransactionInnerTx::Tx(TransactionV1Envelope {
+                tx: tx.clone(),
+                signatures: envelope_signatures(&tx_env).try_into().unwrap(),
+            })
+        );
+        // Never bid less than the inner transaction
+        let bumped = fee_bump_transaction(&tx_env, fee_source, 10).unwrap();
+        let TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope { tx: fee_bump, .. }) =
+            &bumped
+        else {
+            panic!("expected a fee bump envelope");
+        };
+        assert_eq!(fee_bump.fee, 1000 + 100 * 2);
+        assert!(fee_bump_transaction(&bumped, fee_bump.fee_source.clone(), 100).is_err());
+    }
+
+    #[test]
+    fn test_sign_and_replace_auth_entries() {
+        use soroban_env_host::xdr::{
+            HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Operation, SequenceNumber,
+            SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        };
+
+        let network_passphrase = "Test SDF Network ; September 2015";
+        let alice = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let invoke_args = InvokeContractArgs {
+            contract_address: ScAddress::Contract(Hash([2; 32])),
+            function_name: "swap".try_into().unwrap(),
+            args: VecM::default(),
+        };
+        let entry = |nonce| SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                address: ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                    alice.verifying_key().to_bytes(),
+                )))),
+                nonce,
+                signature_expiration_ledger: 0,
+                signature: ScVal::Void,
+            }),
+            root_invocation: SorobanAuthorizedInvocation {
+                function: SorobanAuthorizedFunction::ContractFn(invoke_args.clone()),
+                sub_invocations: VecM::default(),
+            },
+        };
+        let mut tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([3; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: soroban_env_host::xdr::Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function: HostFunction::InvokeContract(invoke_args.clone()),
+                    auth: vec![entry(7)].try_into().unwrap(),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V0,
+        };
+
+        let [unsigned] = address_auth_entries(&tx).try_into().unwrap();
+        assert_eq!(
+            auth_entry_account(&unsigned),
+            Some(&Uint256(alice.verifying_key().to_bytes()))
+        );
+        let signer = crate::signer::Signer::Local(alice.clone());
+        let signed = signer
+            .sign_auth_entry(&unsigned, 1000, network_passphrase)
+            .unwrap();
+        let SorobanCredentials::Address(credentials) = &signed.credentials else {
+            panic!("expected address credentials");
+        };
+        assert_eq!(credentials.signature_expiration_ledger, 1000);
+        assert_ne!(credentials.signature, ScVal::Void);
+
+        let other_simulation = signer
+            .sign_auth_entry(&entry(8), 1000, network_passphrase)
+            .unwrap();
+        let unmatched =
+            replace_auth_entries(&mut tx, &[signed.clone(), other_simulation.clone()]).unwrap();
+        assert_eq!(unmatched, [other_simulation]);
+        assert_eq!(address_auth_entries(&tx), [signed]);
+    }
 }